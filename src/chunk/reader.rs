@@ -1,6 +1,6 @@
 //! Reads chunked messages from an async byte stream.
 
-use bytes::BytesMut;
+use bytes::{Buf, BytesMut};
 use tokio::io::{AsyncRead, AsyncReadExt};
 
 use crate::error::BoltError;
@@ -11,10 +11,17 @@ const MAX_CHUNK_SIZE: usize = 65535;
 /// Default maximum message size: 16 MiB.
 const DEFAULT_MAX_MESSAGE_SIZE: usize = 16 * 1024 * 1024;
 
+/// Minimum spare buffer capacity reserved before each read.
+const READ_SIZE: usize = 8 * 1024;
+
 /// Reads Bolt-chunked messages from an `AsyncRead` stream.
 ///
 /// Each message consists of one or more chunks (2-byte big-endian length prefix
 /// followed by that many data bytes), terminated by a zero-length chunk (0x0000).
+///
+/// Bytes are buffered internally until a complete message is available, so
+/// [`read_message`](Self::read_message) is cancel safe: dropping it mid-message
+/// (e.g. in `tokio::select!`) loses no data.
 pub struct ChunkReader<R> {
     reader: R,
     buf: BytesMut,
@@ -40,33 +47,56 @@ impl<R: AsyncRead + Unpin> ChunkReader<R> {
 
     /// Reads a complete message (all chunks until the `0x0000` terminator).
     pub async fn read_message(&mut self) -> Result<BytesMut, BoltError> {
-        let mut message = BytesMut::new();
+        loop {
+            if let Some(message) = self.take_message()? {
+                return Ok(message);
+            }
+            self.buf.reserve(READ_SIZE);
+            if self.reader.read_buf(&mut self.buf).await? == 0 {
+                return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+            }
+        }
+    }
 
+    /// Removes the first complete message from the buffer, if there is one.
+    fn take_message(&mut self) -> Result<Option<BytesMut>, BoltError> {
+        // Scan chunk headers without consuming until the terminator is buffered.
+        let mut pos = 0;
+        let mut size = 0;
         loop {
-            // Read 2-byte chunk length.
-            let mut header = [0u8; 2];
-            self.reader.read_exact(&mut header).await?;
-            let chunk_len = u16::from_be_bytes(header) as usize;
+            let Some(header) = self.buf.get(pos..pos + 2) else {
+                return Ok(None);
+            };
+            let chunk_len = u16::from_be_bytes([header[0], header[1]]) as usize;
+            pos += 2;
 
             if chunk_len == 0 {
                 // End of message.
                 break;
             }
 
-            if message.len() + chunk_len > self.max_message_size {
+            size += chunk_len;
+            if size > self.max_message_size {
                 return Err(BoltError::Protocol(format!(
                     "message size exceeds limit of {} bytes",
                     self.max_message_size
                 )));
             }
-
-            // Read chunk data.
-            self.buf.resize(chunk_len, 0);
-            self.reader.read_exact(&mut self.buf[..chunk_len]).await?;
-            message.extend_from_slice(&self.buf[..chunk_len]);
+            if self.buf.len() < pos + chunk_len {
+                return Ok(None);
+            }
+            pos += chunk_len;
         }
 
-        Ok(message)
+        // Strip chunk headers.
+        let mut raw = self.buf.split_to(pos);
+        let mut message = BytesMut::with_capacity(size);
+        while raw.len() > 2 {
+            let chunk_len = u16::from_be_bytes([raw[0], raw[1]]) as usize;
+            message.extend_from_slice(&raw[2..2 + chunk_len]);
+            raw.advance(2 + chunk_len);
+        }
+        Ok(Some(message))
     }
 }
 
@@ -74,6 +104,7 @@ impl<R: AsyncRead + Unpin> ChunkReader<R> {
 mod tests {
     use super::*;
     use std::io::Cursor;
+    use tokio::io::AsyncWriteExt;
 
     #[tokio::test]
     async fn read_single_chunk_message() {
@@ -124,4 +155,33 @@ mod tests {
         let err = result.unwrap_err().to_string();
         assert!(err.contains("exceeds limit"), "unexpected error: {err}");
     }
+
+    #[tokio::test]
+    async fn read_back_to_back_messages() {
+        let data: Vec<u8> = vec![
+            0x00, 0x01, 0xAA, 0x00, 0x00, // message 1
+            0x00, 0x00, // NOOP
+            0x00, 0x01, 0xBB, 0x00, 0x00, // message 2
+        ];
+        let mut reader = ChunkReader::new(Cursor::new(data));
+        assert_eq!(&reader.read_message().await.unwrap()[..], &[0xAA]);
+        assert!(reader.read_message().await.unwrap().is_empty());
+        assert_eq!(&reader.read_message().await.unwrap()[..], &[0xBB]);
+        assert!(reader.read_message().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn read_is_cancel_safe() {
+        let (mut tx, rx) = tokio::io::duplex(64);
+        let mut reader = ChunkReader::new(rx);
+
+        // Deliver half a message, then cancel the pending read.
+        tx.write_all(&[0x00, 0x02, 0xAA]).await.unwrap();
+        let timed_out =
+            tokio::time::timeout(std::time::Duration::from_millis(20), reader.read_message()).await;
+        assert!(timed_out.is_err());
+
+        tx.write_all(&[0xBB, 0x00, 0x00]).await.unwrap();
+        assert_eq!(&reader.read_message().await.unwrap()[..], &[0xAA, 0xBB]);
+    }
 }
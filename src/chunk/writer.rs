@@ -41,6 +41,13 @@ impl<W: AsyncWrite + Unpin> ChunkWriter<W> {
         Ok(())
     }
 
+    /// Writes a NOOP: a lone `0x0000` chunk, which peers skip. Keeps an
+    /// idle connection alive.
+    pub async fn write_noop(&mut self) -> Result<(), BoltError> {
+        self.writer.write_all(&[0x00, 0x00]).await?;
+        Ok(())
+    }
+
     /// Flushes the underlying writer.
     pub async fn flush(&mut self) -> Result<(), BoltError> {
         self.writer.flush().await?;
@@ -0,0 +1,82 @@
+//! Credentials for client sessions, and refreshing them when they expire.
+
+use crate::error::BoltError;
+
+/// Credentials sent in LOGON.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthToken {
+    pub scheme: String,
+    pub principal: Option<String>,
+    pub credentials: Option<String>,
+}
+
+impl AuthToken {
+    /// No authentication (scheme `none`).
+    pub fn none() -> Self {
+        Self {
+            scheme: "none".into(),
+            principal: None,
+            credentials: None,
+        }
+    }
+
+    /// Username and password (scheme `basic`).
+    pub fn basic(username: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            scheme: "basic".into(),
+            principal: Some(username.into()),
+            credentials: Some(password.into()),
+        }
+    }
+
+    /// A bearer token such as a JWT (scheme `bearer`).
+    pub fn bearer(token: impl Into<String>) -> Self {
+        Self {
+            scheme: "bearer".into(),
+            principal: None,
+            credentials: Some(token.into()),
+        }
+    }
+}
+
+/// Supplies the credentials a [`BoltSession`](crate::client::BoltSession)
+/// logs on with, and fresh ones when the server reports them expired.
+///
+/// When a request fails with `Neo.ClientError.Security.TokenExpired`, the
+/// session calls [`on_token_expired`](Self::on_token_expired), fetches a new
+/// token with [`get_token`](Self::get_token), re-authenticates with LOGOFF
+/// and LOGON, and retries the request (unless it was part of an explicit
+/// transaction, which the server has rolled back).
+///
+/// ```rust
+/// use boltr::client::{AuthToken, AuthTokenManager};
+/// use boltr::error::BoltError;
+///
+/// struct FromEnv;
+///
+/// #[async_trait::async_trait]
+/// impl AuthTokenManager for FromEnv {
+///     async fn get_token(&self) -> Result<AuthToken, BoltError> {
+///         let token = std::env::var("GRAPH_TOKEN")
+///             .map_err(|e| BoltError::Authentication(e.to_string()))?;
+///         Ok(AuthToken::bearer(token))
+///     }
+/// }
+/// ```
+#[async_trait::async_trait]
+pub trait AuthTokenManager: Send + Sync + 'static {
+    /// Returns the credentials to log on with.
+    async fn get_token(&self) -> Result<AuthToken, BoltError>;
+
+    /// Called when the server rejected `token` as expired, before the next
+    /// [`get_token`](Self::get_token). Default: does nothing.
+    async fn on_token_expired(&self, _token: &AuthToken) {}
+}
+
+/// A fixed token, never refreshed.
+#[async_trait::async_trait]
+impl AuthTokenManager for AuthToken {
+    async fn get_token(&self) -> Result<AuthToken, BoltError> {
+        Ok(self.clone())
+    }
+}
@@ -0,0 +1,67 @@
+//! Bookmarks shared between client sessions for causal chaining.
+
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+/// Collects the bookmarks of committed transactions so later transactions,
+/// in any session sharing the manager, see their writes.
+///
+/// Sessions call [`get_bookmarks`](Self::get_bookmarks) before BEGIN,
+/// auto-commit RUN and ROUTE, and
+/// [`update_bookmarks`](Self::update_bookmarks) after every commit.
+#[async_trait::async_trait]
+pub trait BookmarkManager: Send + Sync + 'static {
+    /// Bookmarks to send with the next transaction.
+    async fn get_bookmarks(&self) -> Vec<String>;
+
+    /// A transaction that started with `previous` committed and produced
+    /// `new`; the previous bookmarks are superseded.
+    async fn update_bookmarks(&self, previous: &[String], new: &[String]);
+}
+
+/// A [`BookmarkManager`] that keeps bookmarks in memory.
+///
+/// ```rust,no_run
+/// # async fn example() -> Result<(), boltr::error::BoltError> {
+/// use std::sync::Arc;
+/// use boltr::client::{BoltSession, InMemoryBookmarkManager};
+///
+/// let bookmarks = Arc::new(InMemoryBookmarkManager::default());
+/// let addr = "127.0.0.1:7687".parse().unwrap();
+/// let mut writer = BoltSession::connect(addr).await?.with_bookmark_manager(bookmarks.clone());
+/// let mut reader = BoltSession::connect(addr).await?.with_bookmark_manager(bookmarks);
+///
+/// writer.run("CREATE (:Person {name: 'Ada'})").await?;
+/// // Sent with the writer's bookmark, so it sees the new node.
+/// reader.run("MATCH (p:Person) RETURN p.name").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct InMemoryBookmarkManager {
+    bookmarks: Mutex<HashSet<String>>,
+}
+
+impl InMemoryBookmarkManager {
+    /// Starts with `bookmarks`, e.g. restored from an earlier run.
+    pub fn new(bookmarks: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            bookmarks: Mutex::new(bookmarks.into_iter().collect()),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl BookmarkManager for InMemoryBookmarkManager {
+    async fn get_bookmarks(&self) -> Vec<String> {
+        self.bookmarks.lock().unwrap().iter().cloned().collect()
+    }
+
+    async fn update_bookmarks(&self, previous: &[String], new: &[String]) {
+        let mut bookmarks = self.bookmarks.lock().unwrap();
+        for bookmark in previous {
+            bookmarks.remove(bookmark);
+        }
+        bookmarks.extend(new.iter().cloned());
+    }
+}
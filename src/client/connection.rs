@@ -1,10 +1,12 @@
 //! Low-level Bolt connection: TCP connect, handshake, message I/O.
 
 use std::collections::HashMap;
-use std::net::SocketAddr;
+use std::future::Future;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::time::Duration;
 
 use bytes::BytesMut;
-use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::io::{AsyncRead, AsyncWrite, BufWriter};
 use tokio::net::TcpStream;
 
 use crate::chunk::reader::ChunkReader;
@@ -15,8 +17,72 @@ use crate::message::encode::encode_client_message;
 use crate::message::request::ClientMessage;
 use crate::message::response::ServerMessage;
 use crate::server::handshake::{client_handshake, default_client_proposals};
+use crate::server::{BoltBackend, BoltService, RoutingServer, RoutingTable};
 use crate::types::{BoltDict, BoltValue};
 
+/// FAILURE code reporting that the credentials of the connection expired.
+pub(crate) const TOKEN_EXPIRED: &str = "Neo.ClientError.Security.TokenExpired";
+
+/// Capacity of each direction of an in-process duplex pipe.
+const IN_PROCESS_BUFFER_SIZE: usize = 64 * 1024;
+
+/// How long a client waits for the server before giving up on the
+/// connection with a `TimedOut` I/O error.
+///
+/// ```rust
+/// use std::time::Duration;
+/// use boltr::client::Timeouts;
+///
+/// let timeouts = Timeouts::new()
+///     .connect(Duration::from_secs(5))
+///     .request(Duration::from_secs(60));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Timeouts {
+    connect: Option<Duration>,
+    handshake: Option<Duration>,
+    request: Option<Duration>,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self {
+            connect: Some(Duration::from_secs(30)),
+            handshake: Some(Duration::from_secs(30)),
+            request: None,
+        }
+    }
+}
+
+impl Timeouts {
+    /// The default timeouts: 30 seconds each to connect and for the
+    /// handshake, and no request timeout. A request timeout, set with
+    /// [`request`](Self::request), is capped by the server's
+    /// `connection.recv_timeout_seconds` hint when the server sends one.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Time to establish the TCP or WebSocket connection. Default: 30 seconds.
+    pub fn connect(mut self, timeout: Duration) -> Self {
+        self.connect = Some(timeout);
+        self
+    }
+
+    /// Time for the Bolt handshake. Default: 30 seconds.
+    pub fn handshake(mut self, timeout: Duration) -> Self {
+        self.handshake = Some(timeout);
+        self
+    }
+
+    /// Time to wait for each message of a reply, and to send a request.
+    /// Default: none, but see [`BoltConnection::set_request_timeout`].
+    pub fn request(mut self, timeout: Duration) -> Self {
+        self.request = Some(timeout);
+        self
+    }
+}
+
 /// A low-level Bolt connection that handles handshake and message framing.
 ///
 /// Internally uses trait objects so the same type works over TCP, TLS,
@@ -25,23 +91,29 @@ pub struct BoltConnection {
     reader: ChunkReader<Box<dyn AsyncRead + Unpin + Send>>,
     writer: ChunkWriter<Box<dyn AsyncWrite + Unpin + Send>>,
     version: (u8, u8),
+    request_timeout: Option<Duration>,
+    /// `connection.recv_timeout_seconds` from the HELLO hints.
+    recv_timeout: Option<Duration>,
+    /// Set by an I/O error or timeout, after which the connection is not
+    /// used again.
+    broken: bool,
 }
 
 impl BoltConnection {
     /// Connects to a Bolt server over TCP, performs the handshake, and returns
     /// a connection ready for HELLO/LOGON.
     pub async fn connect(addr: SocketAddr) -> Result<Self, BoltError> {
-        let mut stream = TcpStream::connect(addr).await?;
-
-        let proposals = default_client_proposals();
-        let version = client_handshake(&mut stream, &proposals).await?;
+        Self::connect_with_timeouts(addr, &Timeouts::default()).await
+    }
 
-        let (rh, wh) = tokio::io::split(stream);
-        Ok(Self {
-            reader: ChunkReader::new(Box::new(rh)),
-            writer: ChunkWriter::new(Box::new(wh)),
-            version,
-        })
+    /// Like [`connect`](Self::connect), with the given timeouts.
+    pub async fn connect_with_timeouts(
+        addr: SocketAddr,
+        timeouts: &Timeouts,
+    ) -> Result<Self, BoltError> {
+        let connect = async { Ok(TcpStream::connect(addr).await?) };
+        let stream = within(timeouts.connect, "connect", connect).await?;
+        Self::from_stream_with_timeouts(stream, timeouts).await
     }
 
     /// Connects to a Bolt server over WebSocket, performs the handshake,
@@ -51,17 +123,67 @@ impl BoltConnection {
     /// by the WebSocket library using platform-default root certificates.
     #[cfg(feature = "ws")]
     pub async fn connect_ws(url: &str) -> Result<Self, BoltError> {
-        let (ws_stream, _response) = tokio_tungstenite::connect_async(url).await?;
-        let mut adapted = crate::ws::WsStream::new(ws_stream);
+        Self::connect_ws_with_timeouts(url, &Timeouts::default()).await
+    }
 
+    /// Like [`connect_ws`](Self::connect_ws), with the given timeouts.
+    #[cfg(feature = "ws")]
+    pub async fn connect_ws_with_timeouts(
+        url: &str,
+        timeouts: &Timeouts,
+    ) -> Result<Self, BoltError> {
+        let connect = async { Ok(tokio_tungstenite::connect_async(url).await?) };
+        let (ws_stream, _response) = within(timeouts.connect, "connect", connect).await?;
+        Self::from_stream_with_timeouts(crate::ws::WsStream::new(ws_stream), timeouts).await
+    }
+
+    /// Connects to a [`BoltService`] in the same process through an in-memory
+    /// duplex pipe, and returns a connection ready for HELLO/LOGON.
+    ///
+    /// The server side runs on a spawned task and goes through the full
+    /// protocol stack (handshake, chunking, state machine); no socket or
+    /// port is involved. The peer address seen by the server is `0.0.0.0:0`.
+    pub async fn connect_in_process<B: BoltBackend>(
+        service: &BoltService<B>,
+    ) -> Result<Self, BoltError> {
+        let (client, server) = tokio::io::duplex(IN_PROCESS_BUFFER_SIZE);
+        let service = service.clone();
+        let peer = SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0));
+        tokio::spawn(async move { service.serve_connection(server, peer).await });
+        Self::from_stream(client).await
+    }
+
+    /// Performs the handshake on an established stream (TCP, TLS, Unix
+    /// socket, in-memory duplex, ...) and returns a connection ready for
+    /// HELLO/LOGON.
+    pub async fn from_stream<S>(stream: S) -> Result<Self, BoltError>
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        Self::from_stream_with_timeouts(stream, &Timeouts::default()).await
+    }
+
+    /// Like [`from_stream`](Self::from_stream), with the given handshake and
+    /// request timeouts.
+    pub async fn from_stream_with_timeouts<S>(
+        mut stream: S,
+        timeouts: &Timeouts,
+    ) -> Result<Self, BoltError>
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
         let proposals = default_client_proposals();
-        let version = client_handshake(&mut adapted, &proposals).await?;
+        let handshake = client_handshake(&mut stream, &proposals);
+        let version = within(timeouts.handshake, "handshake", handshake).await?;
 
-        let (rh, wh) = tokio::io::split(adapted);
+        let (rh, wh) = tokio::io::split(stream);
         Ok(Self {
             reader: ChunkReader::new(Box::new(rh)),
-            writer: ChunkWriter::new(Box::new(wh)),
+            writer: ChunkWriter::new(Box::new(BufWriter::new(wh))),
             version,
+            request_timeout: timeouts.request,
+            recv_timeout: None,
+            broken: false,
         })
     }
 
@@ -70,24 +192,126 @@ impl BoltConnection {
         self.version
     }
 
+    /// Sets how long to wait for each message of a reply, and to send a
+    /// request. If the server hints a shorter `connection.recv_timeout_seconds`
+    /// in its HELLO reply, replies use that. A timeout leaves the
+    /// connection broken: every later call fails.
+    pub fn set_request_timeout(&mut self, timeout: Option<Duration>) {
+        self.request_timeout = timeout;
+    }
+
     /// Sends a client message.
     pub async fn send(&mut self, msg: &ClientMessage) -> Result<(), BoltError> {
+        self.queue(msg).await?;
+        self.flush().await
+    }
+
+    /// Buffers a client message to be sent with the next
+    /// [`flush`](Self::flush), so several requests go out in one write.
+    /// The server answers them in order.
+    pub async fn queue(&mut self, msg: &ClientMessage) -> Result<(), BoltError> {
         let mut buf = BytesMut::new();
         encode_client_message(&mut buf, msg);
-        self.writer.write_message(&buf).await
+        let write = self.writer.write_message(&buf);
+        let result = within(self.request_timeout, "send", write).await;
+        self.check(result)
     }
 
-    /// Receives a server message.
+    /// Sends the queued messages.
+    pub async fn flush(&mut self) -> Result<(), BoltError> {
+        let result = within(self.request_timeout, "send", self.writer.flush()).await;
+        self.check(result)
+    }
+
+    /// Sends a NOOP chunk, e.g. to keep an idle connection from being
+    /// closed by the server or a proxy in between.
+    pub async fn send_noop(&mut self) -> Result<(), BoltError> {
+        let write = self.writer.write_noop();
+        let result = within(self.request_timeout, "send", write).await;
+        self.check(result)?;
+        self.flush().await
+    }
+
+    /// Receives a server message, skipping NOOP keep-alives.
     pub async fn recv(&mut self) -> Result<ServerMessage, BoltError> {
-        let data = self.reader.read_message().await?;
-        decode_server_message(&data)
+        let timeout = match (self.request_timeout, self.recv_timeout) {
+            (Some(request), Some(recv)) => Some(request.min(recv)),
+            (request, recv) => request.or(recv),
+        };
+        loop {
+            let read = self.reader.read_message();
+            let result = within(timeout, "waiting for the server", read).await;
+            let data = self.check(result)?;
+            if !data.is_empty() {
+                return decode_server_message(&data);
+            }
+        }
+    }
+
+    /// Fails if the connection is broken, and marks it broken on an I/O
+    /// error.
+    fn check<T>(&mut self, result: Result<T, BoltError>) -> Result<T, BoltError> {
+        if self.broken {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotConnected,
+                "connection broken by an earlier I/O error",
+            )
+            .into());
+        }
+        if let Err(BoltError::Io(_)) = result {
+            self.broken = true;
+        }
+        result
+    }
+
+    /// Receives the reply to `request`: the SUCCESS metadata, or a FAILURE
+    /// as [`BoltError::Query`]. An IGNORED reply, to a request sent after
+    /// a failure, is a protocol error.
+    pub async fn recv_summary(&mut self, request: &str) -> Result<BoltDict, BoltError> {
+        match self.recv().await? {
+            ServerMessage::Success { metadata } => Ok(metadata),
+            ServerMessage::Failure { metadata } => {
+                Err(query_failure(&metadata, &format!("{request} failed")))
+            }
+            other => Err(BoltError::Protocol(format!(
+                "expected SUCCESS after {request}, got {other:?}"
+            ))),
+        }
+    }
+
+    /// Receives the records and the summary that answer a PULL or DISCARD.
+    pub async fn recv_records(&mut self) -> Result<(Vec<Vec<BoltValue>>, BoltDict), BoltError> {
+        let mut records = Vec::new();
+        loop {
+            match self.recv().await? {
+                ServerMessage::Record { data } => records.push(data),
+                ServerMessage::Success { metadata } => return Ok((records, metadata)),
+                ServerMessage::Failure { metadata } => {
+                    return Err(query_failure(&metadata, "pull failed"));
+                }
+                other => {
+                    return Err(BoltError::Protocol(format!(
+                        "unexpected message during PULL: {other:?}"
+                    )));
+                }
+            }
+        }
     }
 
     /// Sends HELLO and expects SUCCESS.
     pub async fn hello(&mut self, extra: BoltDict) -> Result<BoltDict, BoltError> {
         self.send(&ClientMessage::Hello { extra }).await?;
         match self.recv().await? {
-            ServerMessage::Success { metadata } => Ok(metadata),
+            ServerMessage::Success { metadata } => {
+                if let Some(BoltValue::Dict(hints)) = metadata.get("hints")
+                    && let Some(&BoltValue::Integer(seconds)) =
+                        hints.get("connection.recv_timeout_seconds")
+                    && seconds > 0
+                {
+                    self.recv_timeout = Some(Duration::from_secs(seconds as u64));
+                }
+                Ok(metadata)
+            }
             ServerMessage::Failure { metadata } => Err(BoltError::Authentication(
                 metadata
                     .get("message")
@@ -170,60 +394,13 @@ impl BoltConnection {
             extra,
         })
         .await?;
-        match self.recv().await? {
-            ServerMessage::Success { metadata } => Ok(metadata),
-            ServerMessage::Failure { metadata } => Err(BoltError::Query {
-                code: metadata
-                    .get("code")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("unknown")
-                    .to_string(),
-                message: metadata
-                    .get("message")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("query failed")
-                    .to_string(),
-            }),
-            other => Err(BoltError::Protocol(format!(
-                "expected SUCCESS after RUN, got {other:?}"
-            ))),
-        }
+        self.recv_summary("RUN").await
     }
 
     /// Sends PULL and collects all records until SUCCESS summary.
     pub async fn pull_all(&mut self) -> Result<(Vec<Vec<BoltValue>>, BoltDict), BoltError> {
         self.send(&ClientMessage::pull_all()).await?;
-
-        let mut records = Vec::new();
-        loop {
-            match self.recv().await? {
-                ServerMessage::Record { data } => {
-                    records.push(data);
-                }
-                ServerMessage::Success { metadata } => {
-                    return Ok((records, metadata));
-                }
-                ServerMessage::Failure { metadata } => {
-                    return Err(BoltError::Query {
-                        code: metadata
-                            .get("code")
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("unknown")
-                            .to_string(),
-                        message: metadata
-                            .get("message")
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("pull failed")
-                            .to_string(),
-                    });
-                }
-                other => {
-                    return Err(BoltError::Protocol(format!(
-                        "unexpected message during PULL: {other:?}"
-                    )));
-                }
-            }
-        }
+        self.recv_records().await
     }
 
     /// Sends PULL requesting `n` records and collects them until SUCCESS.
@@ -232,60 +409,14 @@ impl BoltConnection {
     /// `metadata["has_more"]` to determine if more records remain.
     pub async fn pull_n(&mut self, n: i64) -> Result<(Vec<Vec<BoltValue>>, BoltDict), BoltError> {
         self.send(&ClientMessage::pull_n(n)).await?;
-
-        let mut records = Vec::new();
-        loop {
-            match self.recv().await? {
-                ServerMessage::Record { data } => {
-                    records.push(data);
-                }
-                ServerMessage::Success { metadata } => {
-                    return Ok((records, metadata));
-                }
-                ServerMessage::Failure { metadata } => {
-                    return Err(BoltError::Query {
-                        code: metadata
-                            .get("code")
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("unknown")
-                            .to_string(),
-                        message: metadata
-                            .get("message")
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("pull failed")
-                            .to_string(),
-                    });
-                }
-                other => {
-                    return Err(BoltError::Protocol(format!(
-                        "unexpected message during PULL: {other:?}"
-                    )));
-                }
-            }
-        }
+        self.recv_records().await
     }
 
     /// Sends DISCARD to skip all remaining records and expects SUCCESS.
-    pub async fn discard_all(&mut self) -> Result<(), BoltError> {
+    /// Returns the SUCCESS metadata, which may contain a `"bookmark"`.
+    pub async fn discard_all(&mut self) -> Result<BoltDict, BoltError> {
         self.send(&ClientMessage::discard_all()).await?;
-        match self.recv().await? {
-            ServerMessage::Success { .. } => Ok(()),
-            ServerMessage::Failure { metadata } => Err(BoltError::Query {
-                code: metadata
-                    .get("code")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("unknown")
-                    .to_string(),
-                message: metadata
-                    .get("message")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("discard failed")
-                    .to_string(),
-            }),
-            other => Err(BoltError::Protocol(format!(
-                "expected SUCCESS after DISCARD, got {other:?}"
-            ))),
-        }
+        self.recv_summary("DISCARD").await
     }
 
     /// Sends BEGIN and expects SUCCESS.
@@ -293,13 +424,9 @@ impl BoltConnection {
         self.send(&ClientMessage::Begin { extra }).await?;
         match self.recv().await? {
             ServerMessage::Success { .. } => Ok(()),
-            ServerMessage::Failure { metadata } => Err(BoltError::Transaction(
-                metadata
-                    .get("message")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("BEGIN failed")
-                    .to_string(),
-            )),
+            ServerMessage::Failure { metadata } => {
+                Err(transaction_failure(&metadata, "BEGIN failed"))
+            }
             other => Err(BoltError::Protocol(format!(
                 "expected SUCCESS after BEGIN, got {other:?}"
             ))),
@@ -311,13 +438,9 @@ impl BoltConnection {
         self.send(&ClientMessage::Commit).await?;
         match self.recv().await? {
             ServerMessage::Success { metadata } => Ok(metadata),
-            ServerMessage::Failure { metadata } => Err(BoltError::Transaction(
-                metadata
-                    .get("message")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("COMMIT failed")
-                    .to_string(),
-            )),
+            ServerMessage::Failure { metadata } => {
+                Err(transaction_failure(&metadata, "COMMIT failed"))
+            }
             other => Err(BoltError::Protocol(format!(
                 "expected SUCCESS after COMMIT, got {other:?}"
             ))),
@@ -342,6 +465,32 @@ impl BoltConnection {
         }
     }
 
+    /// Sends ROUTE and returns the routing table for `extra["db"]` (or the
+    /// home database).
+    pub async fn route(
+        &mut self,
+        routing: BoltDict,
+        bookmarks: Vec<String>,
+        extra: BoltDict,
+    ) -> Result<RoutingTable, BoltError> {
+        self.send(&ClientMessage::Route {
+            routing,
+            bookmarks,
+            extra,
+        })
+        .await?;
+        match self.recv().await? {
+            ServerMessage::Success { metadata } => match metadata.get("rt") {
+                Some(BoltValue::Dict(rt)) => parse_routing_table(rt),
+                _ => Err(BoltError::Protocol("ROUTE reply without rt".into())),
+            },
+            ServerMessage::Failure { metadata } => Err(query_failure(&metadata, "ROUTE failed")),
+            other => Err(BoltError::Protocol(format!(
+                "expected SUCCESS after ROUTE, got {other:?}"
+            ))),
+        }
+    }
+
     /// Sends RESET and expects SUCCESS.
     pub async fn reset(&mut self) -> Result<(), BoltError> {
         self.send(&ClientMessage::Reset).await?;
@@ -360,3 +509,100 @@ impl BoltConnection {
         }
     }
 }
+
+/// Runs `io`, failing with a `TimedOut` I/O error after `timeout`.
+async fn within<T>(
+    timeout: Option<Duration>,
+    what: &str,
+    io: impl Future<Output = Result<T, BoltError>>,
+) -> Result<T, BoltError> {
+    let Some(timeout) = timeout else {
+        return io.await;
+    };
+    tokio::time::timeout(timeout, io).await.unwrap_or_else(|_| {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::TimedOut,
+            format!("{what} timed out after {timeout:?}"),
+        )
+        .into())
+    })
+}
+
+fn query_failure(metadata: &BoltDict, default: &str) -> BoltError {
+    BoltError::Query {
+        code: metadata
+            .get("code")
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown")
+            .to_string(),
+        message: metadata
+            .get("message")
+            .and_then(|v| v.as_str())
+            .unwrap_or(default)
+            .to_string(),
+    }
+}
+
+/// Maps a BEGIN or COMMIT FAILURE to [`BoltError::Transaction`], keeping
+/// the code of expired credentials and of retryable failures.
+fn transaction_failure(metadata: &BoltDict, default: &str) -> BoltError {
+    let message = metadata
+        .get("message")
+        .and_then(|v| v.as_str())
+        .unwrap_or(default)
+        .to_string();
+    match metadata.get("code").and_then(|v| v.as_str()) {
+        Some(TOKEN_EXPIRED) => BoltError::TokenExpired(message),
+        Some(code) => {
+            let error = BoltError::Query {
+                code: code.to_string(),
+                message: message.clone(),
+            };
+            if error.is_retryable() {
+                error
+            } else {
+                BoltError::Transaction(message)
+            }
+        }
+        None => BoltError::Transaction(message),
+    }
+}
+
+fn parse_routing_table(rt: &BoltDict) -> Result<RoutingTable, BoltError> {
+    let invalid = || BoltError::Protocol("malformed routing table".into());
+    let servers = match rt.get("servers") {
+        Some(BoltValue::List(servers)) => servers
+            .iter()
+            .map(|server| {
+                let BoltValue::Dict(server) = server else {
+                    return Err(invalid());
+                };
+                let addresses = match server.get("addresses") {
+                    Some(BoltValue::List(addresses)) => addresses
+                        .iter()
+                        .filter_map(|a| a.as_str().map(String::from))
+                        .collect(),
+                    _ => return Err(invalid()),
+                };
+                let role = server
+                    .get("role")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(invalid)?;
+                Ok(RoutingServer {
+                    addresses,
+                    role: role.to_string(),
+                })
+            })
+            .collect::<Result<_, _>>()?,
+        _ => return Err(invalid()),
+    };
+    Ok(RoutingTable {
+        ttl: rt.get("ttl").and_then(|v| v.as_int()).ok_or_else(invalid)?,
+        db: rt
+            .get("db")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string(),
+        servers,
+    })
+}
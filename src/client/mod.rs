@@ -2,8 +2,20 @@
 //!
 //! Feature-gated behind `client`. Primarily intended for integration testing.
 
+mod auth;
+mod bookmarks;
 mod connection;
+mod pipeline;
+mod record;
+mod retry;
 mod session;
+mod stream;
 
-pub use connection::BoltConnection;
-pub use session::BoltSession;
+pub use auth::{AuthToken, AuthTokenManager};
+pub use bookmarks::{BookmarkManager, InMemoryBookmarkManager};
+pub use connection::{BoltConnection, Timeouts};
+pub use pipeline::Pipeline;
+pub use record::{FromRecord, Record};
+pub use retry::RetryPolicy;
+pub use session::{BoltSession, ManagedTransaction, QueryResult};
+pub use stream::RecordStream;
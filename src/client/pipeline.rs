@@ -0,0 +1,80 @@
+//! Pipelined queries.
+
+use std::collections::HashMap;
+
+use crate::error::BoltError;
+use crate::types::{BoltDict, BoltValue};
+
+use super::session::{BoltSession, QueryResult};
+
+/// Queries sent to the server in one write and answered in one round trip.
+///
+/// Outside an explicit transaction, the queries run in a transaction of
+/// their own: BEGIN, a RUN and PULL per query, and COMMIT all go out
+/// together. If one fails, the server IGNOREs the rest, the transaction is
+/// rolled back and the first failure is returned. In a transaction opened
+/// with [`BoltSession::begin`], only the RUNs and PULLs are sent, and the
+/// transaction stays open.
+///
+/// ```rust,no_run
+/// # async fn example() -> Result<(), boltr::error::BoltError> {
+/// use boltr::client::BoltSession;
+///
+/// let mut session = BoltSession::connect("127.0.0.1:7687".parse().unwrap()).await?;
+/// let results = session
+///     .pipeline()
+///     .run("CREATE (:Person {name: 'Ada'})")
+///     .run("CREATE (:Person {name: 'Alan'})")
+///     .run("MATCH (p:Person) RETURN count(p) AS people")
+///     .execute()
+///     .await?;
+/// println!("{:?}", results[2].records[0].get("people"));
+/// # Ok(())
+/// # }
+/// ```
+#[must_use = "a pipeline does nothing until executed"]
+pub struct Pipeline<'a> {
+    session: &'a mut BoltSession,
+    statements: Vec<Statement>,
+}
+
+/// A query queued in a [`Pipeline`].
+pub(super) struct Statement {
+    pub(super) query: String,
+    pub(super) params: HashMap<String, BoltValue>,
+    pub(super) extra: BoltDict,
+}
+
+impl<'a> Pipeline<'a> {
+    pub(super) fn new(session: &'a mut BoltSession) -> Self {
+        Self {
+            session,
+            statements: Vec::new(),
+        }
+    }
+
+    /// Queues a query.
+    pub fn run(self, query: &str) -> Self {
+        self.run_with_params(query, HashMap::new(), BoltDict::new())
+    }
+
+    /// Queues a query with parameters and extra RUN metadata.
+    pub fn run_with_params(
+        mut self,
+        query: &str,
+        params: HashMap<String, BoltValue>,
+        extra: BoltDict,
+    ) -> Self {
+        self.statements.push(Statement {
+            query: query.to_string(),
+            params,
+            extra,
+        });
+        self
+    }
+
+    /// Sends the queries and returns their results, in order.
+    pub async fn execute(self) -> Result<Vec<QueryResult>, BoltError> {
+        self.session.run_pipeline(&self.statements).await
+    }
+}
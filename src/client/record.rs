@@ -0,0 +1,128 @@
+//! Query result records with named columns.
+
+use std::sync::Arc;
+
+use crate::error::BoltError;
+use crate::types::{BoltDict, BoltValue, FromBoltValue};
+
+/// A row of a query result. Records of the same result share their column
+/// names.
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use boltr::client::{FromRecord, Record};
+/// use boltr::error::BoltError;
+/// use boltr::types::BoltValue;
+///
+/// struct Person {
+///     name: String,
+///     age: Option<i64>,
+/// }
+///
+/// impl FromRecord for Person {
+///     fn from_record(record: &Record) -> Result<Self, BoltError> {
+///         Ok(Self {
+///             name: record.get_typed("name")?,
+///             age: record.get_typed("age")?,
+///         })
+///     }
+/// }
+///
+/// let keys: Arc<[String]> = Arc::from(["name".to_string(), "age".to_string()]);
+/// let record = Record::new(keys, vec!["Ada".into(), BoltValue::Null]);
+/// assert_eq!(record.get("name"), Some(&BoltValue::from("Ada")));
+///
+/// let person: Person = record.to()?;
+/// assert_eq!((person.name.as_str(), person.age), ("Ada", None));
+/// # Ok::<(), BoltError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    keys: Arc<[String]>,
+    values: Vec<BoltValue>,
+}
+
+impl Record {
+    /// Pairs `values` with the column names `keys`, position by position.
+    pub fn new(keys: Arc<[String]>, values: Vec<BoltValue>) -> Self {
+        Self { keys, values }
+    }
+
+    /// Column names.
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    /// Values, in column order.
+    pub fn values(&self) -> &[BoltValue] {
+        &self.values
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// The value of column `key`.
+    pub fn get(&self, key: &str) -> Option<&BoltValue> {
+        let index = self.keys.iter().position(|k| k == key)?;
+        self.values.get(index)
+    }
+
+    /// The value of column `key` converted to `T`. Fails with
+    /// [`BoltError::Conversion`] if there is no such column or the value
+    /// has another type.
+    pub fn get_typed<T: FromBoltValue>(&self, key: &str) -> Result<T, BoltError> {
+        let value = self
+            .get(key)
+            .ok_or_else(|| BoltError::Conversion(format!("no column {key:?}")))?;
+        T::from_bolt(value.clone())
+            .map_err(|e| BoltError::Conversion(format!("column {key:?}: {e}")))
+    }
+
+    /// (column name, value) pairs, in column order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &BoltValue)> {
+        self.keys.iter().map(String::as_str).zip(&self.values)
+    }
+
+    /// Converts the record with [`FromRecord`].
+    pub fn to<T: FromRecord>(&self) -> Result<T, BoltError> {
+        T::from_record(self)
+    }
+
+    /// The record as a map from column name to value.
+    pub fn into_map(self) -> BoltDict {
+        self.into_iter().collect()
+    }
+}
+
+impl IntoIterator for Record {
+    type Item = (String, BoltValue);
+    type IntoIter = std::vec::IntoIter<(String, BoltValue)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let pairs: Vec<_> = self.keys.iter().cloned().zip(self.values).collect();
+        pairs.into_iter()
+    }
+}
+
+impl From<Record> for BoltDict {
+    fn from(record: Record) -> Self {
+        record.into_map()
+    }
+}
+
+/// Builds a type, typically a user struct, from a [`Record`], usually with
+/// [`Record::get_typed`] per field. See [`Record`] for an example.
+pub trait FromRecord: Sized {
+    fn from_record(record: &Record) -> Result<Self, BoltError>;
+}
+
+impl FromRecord for BoltDict {
+    fn from_record(record: &Record) -> Result<Self, BoltError> {
+        Ok(record.clone().into_map())
+    }
+}
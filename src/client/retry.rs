@@ -0,0 +1,108 @@
+//! Retry policy for managed transactions.
+
+use std::time::Duration;
+
+/// How [`BoltSession::execute_read`](super::BoltSession::execute_read) and
+/// [`execute_write`](super::BoltSession::execute_write) retry transactions
+/// that fail with a [retryable](crate::error::BoltError::is_retryable) error.
+///
+/// The delay before retry `n` is `initial_delay * multiplier^n`, varied by
+/// up to `±jitter` of itself so that competing clients spread out. No retry
+/// starts once `max_retry_time` has passed since the first attempt.
+///
+/// ```rust
+/// use std::time::Duration;
+/// use boltr::client::RetryPolicy;
+///
+/// let policy = RetryPolicy::new()
+///     .max_retry_time(Duration::from_secs(10))
+///     .initial_delay(Duration::from_millis(100));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_retry_time: Duration,
+    initial_delay: Duration,
+    multiplier: f64,
+    jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retry_time: Duration::from_secs(30),
+            initial_delay: Duration::from_secs(1),
+            multiplier: 2.0,
+            jitter: 0.2,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The default policy: the first retry after 1 second, each delay twice
+    /// the previous one and varied by ±20%, and no retry starting later
+    /// than 30 seconds after the first attempt. Retries are bounded by time
+    /// rather than by count.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Time after the first attempt during which retries may start.
+    /// Default: 30 seconds. Zero disables retries.
+    pub fn max_retry_time(mut self, time: Duration) -> Self {
+        self.max_retry_time = time;
+        self
+    }
+
+    /// Delay before the first retry. Default: 1 second.
+    pub fn initial_delay(mut self, delay: Duration) -> Self {
+        self.initial_delay = delay;
+        self
+    }
+
+    /// Factor by which the delay grows with each retry. Default: 2.
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    /// Fraction by which each delay is varied at random, from 0 to 1.
+    /// Default: 0.2.
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// The delay before retry `retry` (0 for the first), or `None` if it
+    /// would start later than `max_retry_time` after the first attempt.
+    pub(crate) fn delay(&self, retry: u32, elapsed: Duration) -> Option<Duration> {
+        let base = self.initial_delay.as_secs_f64() * self.multiplier.powi(retry as i32);
+        let delay =
+            Duration::try_from_secs_f64(base * (1.0 + self.jitter * (2.0 * fastrand::f64() - 1.0)))
+                .unwrap_or(self.max_retry_time);
+        (elapsed + delay <= self.max_retry_time).then_some(delay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delays_grow_with_jitter_until_the_deadline() {
+        let policy = RetryPolicy::new()
+            .max_retry_time(Duration::from_secs(10))
+            .initial_delay(Duration::from_secs(1));
+        for (retry, expected) in [(0, 1.0), (1, 2.0), (2, 4.0)] {
+            let delay = policy.delay(retry, Duration::ZERO).unwrap().as_secs_f64();
+            assert!(
+                (expected * 0.8..=expected * 1.2).contains(&delay),
+                "{delay}"
+            );
+        }
+        assert_eq!(policy.delay(4, Duration::ZERO), None);
+        assert_eq!(policy.delay(0, Duration::from_millis(9500)), None);
+
+        let exact = policy.jitter(0.0);
+        assert_eq!(exact.delay(1, Duration::ZERO), Some(Duration::from_secs(2)));
+    }
+}
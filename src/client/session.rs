@@ -2,11 +2,21 @@
 
 use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use crate::error::BoltError;
-use crate::types::{BoltDict, BoltValue};
+use crate::message::request::ClientMessage;
+use crate::server::{BoltBackend, BoltService, RoutingTable, extract_bookmarks};
+use crate::types::{BoltDict, BoltValue, ResultSummary};
 
-use super::connection::BoltConnection;
+use super::auth::{AuthToken, AuthTokenManager};
+use super::bookmarks::BookmarkManager;
+use super::connection::{BoltConnection, TOKEN_EXPIRED, Timeouts};
+use super::pipeline::{Pipeline, Statement};
+use super::record::Record;
+use super::retry::RetryPolicy;
+use super::stream::RecordStream;
 
 /// A high-level Bolt session that handles connection, authentication,
 /// and provides a convenient query API.
@@ -26,7 +36,11 @@ use super::connection::BoltConnection;
 /// // Explicit transaction
 /// session.begin().await?;
 /// session.run("CREATE (n:Test {val: 1})").await?;
-/// let bookmark = session.commit().await?;
+/// session.commit().await?;
+///
+/// // Later transactions and auto-commit queries carry the bookmark of
+/// // the last commit, so they see its writes.
+/// println!("bookmarks: {:?}", session.last_bookmarks());
 ///
 /// session.close().await?;
 /// # Ok(())
@@ -34,19 +48,65 @@ use super::connection::BoltConnection;
 /// ```
 pub struct BoltSession {
     conn: BoltConnection,
+    /// Credentials of the last LOGON.
+    token: AuthToken,
+    /// Refreshes `token` when the server reports it expired.
+    token_manager: Option<Arc<dyn AuthTokenManager>>,
+    in_transaction: bool,
+    /// Bookmarks of the last commit in this session.
+    bookmarks: Vec<String>,
+    /// Bookmarks the open transaction was started with.
+    tx_bookmarks: Vec<String>,
+    bookmark_manager: Option<Arc<dyn BookmarkManager>>,
+    retry_policy: RetryPolicy,
+    /// Records per PULL of a [`RecordStream`].
+    fetch_size: i64,
+    /// What a dropped record stream, or a fetch interrupted midway, left
+    /// on the connection.
+    abandoned: Option<Abandoned>,
+}
+
+/// What a [`RecordStream`] dropped before its end left on the connection.
+pub(super) enum Abandoned {
+    /// Records to DISCARD; bookmarks sent with an auto-commit RUN.
+    Result(Option<Vec<String>>),
+    /// The unread rest of the reply to a PULL or DISCARD, then possibly
+    /// records to DISCARD.
+    Pulled(Option<Vec<String>>),
+    /// A partly sent request.
+    Request,
 }
 
 impl BoltSession {
     /// Connects and authenticates (HELLO + LOGON with "none" scheme).
     pub async fn connect(addr: SocketAddr) -> Result<Self, BoltError> {
-        let mut conn = BoltConnection::connect(addr).await?;
-        let extra = BoltDict::from([(
-            "user_agent".to_string(),
-            BoltValue::String("boltr-client/0.2.0".to_string()),
-        )]);
-        conn.hello(extra).await?;
-        conn.logon("none", None, None).await?;
-        Ok(Self { conn })
+        Self::authenticate(BoltConnection::connect(addr).await?, None).await
+    }
+
+    /// Connects and authenticates with `token`, with the given timeouts.
+    /// The request timeout applies to HELLO and LOGON too.
+    ///
+    /// ```rust,no_run
+    /// # async fn example() -> Result<(), boltr::error::BoltError> {
+    /// use std::time::Duration;
+    /// use boltr::client::{AuthToken, BoltSession, Timeouts};
+    ///
+    /// let timeouts = Timeouts::new()
+    ///     .connect(Duration::from_secs(5))
+    ///     .request(Duration::from_secs(60));
+    /// let token = AuthToken::basic("neo4j", "secret");
+    /// let addr = "127.0.0.1:7687".parse().unwrap();
+    /// let mut session = BoltSession::connect_with_timeouts(addr, token, &timeouts).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn connect_with_timeouts(
+        addr: SocketAddr,
+        token: AuthToken,
+        timeouts: &Timeouts,
+    ) -> Result<Self, BoltError> {
+        let conn = BoltConnection::connect_with_timeouts(addr, timeouts).await?;
+        Self::start(conn, token, None).await
     }
 
     /// Connects over WebSocket and authenticates (HELLO + LOGON with "none" scheme).
@@ -54,14 +114,19 @@ impl BoltSession {
     /// Accepts `ws://` and `wss://` URLs.
     #[cfg(feature = "ws")]
     pub async fn connect_ws(url: &str) -> Result<Self, BoltError> {
-        let mut conn = BoltConnection::connect_ws(url).await?;
-        let extra = BoltDict::from([(
-            "user_agent".to_string(),
-            BoltValue::String("boltr-client/0.2.0".to_string()),
-        )]);
-        conn.hello(extra).await?;
-        conn.logon("none", None, None).await?;
-        Ok(Self { conn })
+        Self::authenticate(BoltConnection::connect_ws(url).await?, None).await
+    }
+
+    /// Like [`connect_with_timeouts`](Self::connect_with_timeouts), over
+    /// WebSocket.
+    #[cfg(feature = "ws")]
+    pub async fn connect_ws_with_timeouts(
+        url: &str,
+        token: AuthToken,
+        timeouts: &Timeouts,
+    ) -> Result<Self, BoltError> {
+        let conn = BoltConnection::connect_ws_with_timeouts(url, timeouts).await?;
+        Self::start(conn, token, None).await
     }
 
     /// Connects over WebSocket and authenticates with basic auth.
@@ -73,14 +138,8 @@ impl BoltSession {
         username: &str,
         password: &str,
     ) -> Result<Self, BoltError> {
-        let mut conn = BoltConnection::connect_ws(url).await?;
-        let extra = BoltDict::from([(
-            "user_agent".to_string(),
-            BoltValue::String("boltr-client/0.2.0".to_string()),
-        )]);
-        conn.hello(extra).await?;
-        conn.logon("basic", Some(username), Some(password)).await?;
-        Ok(Self { conn })
+        let conn = BoltConnection::connect_ws(url).await?;
+        Self::authenticate(conn, Some((username, password))).await
     }
 
     /// Connects and authenticates with basic auth.
@@ -89,14 +148,220 @@ impl BoltSession {
         username: &str,
         password: &str,
     ) -> Result<Self, BoltError> {
-        let mut conn = BoltConnection::connect(addr).await?;
+        let conn = BoltConnection::connect(addr).await?;
+        Self::authenticate(conn, Some((username, password))).await
+    }
+
+    /// Connects and authenticates with credentials from `manager`, which
+    /// are refreshed transparently when they expire.
+    pub async fn connect_with_auth(
+        addr: SocketAddr,
+        manager: Arc<dyn AuthTokenManager>,
+    ) -> Result<Self, BoltError> {
+        Self::with_auth(BoltConnection::connect(addr).await?, manager).await
+    }
+
+    /// Authenticates an established connection (from any transport) with
+    /// credentials from `manager`, which are refreshed transparently when
+    /// they expire. Connect with
+    /// [`BoltConnection::connect_with_timeouts`] to set timeouts.
+    pub async fn with_auth(
+        conn: BoltConnection,
+        manager: Arc<dyn AuthTokenManager>,
+    ) -> Result<Self, BoltError> {
+        let token = manager.get_token().await?;
+        Self::start(conn, token, Some(manager)).await
+    }
+
+    /// Authenticates an established connection with `token`, e.g. one made
+    /// with [`BoltConnection::connect_with_timeouts`].
+    pub async fn with_token(conn: BoltConnection, token: AuthToken) -> Result<Self, BoltError> {
+        Self::start(conn, token, None).await
+    }
+
+    /// Connects to an in-process [`BoltService`] over an in-memory pipe and
+    /// authenticates (HELLO + LOGON with "none" scheme).
+    ///
+    /// Exercises the real protocol stack without a socket, which makes it
+    /// handy for testing a [`BoltBackend`](crate::server::BoltBackend) end to end:
+    ///
+    /// ```rust,no_run
+    /// use boltr::client::BoltSession;
+    /// use boltr::server::BoltServer;
+    /// # use boltr::server::BoltBackend;
+    ///
+    /// # async fn example(backend: impl BoltBackend) -> Result<(), boltr::error::BoltError> {
+    /// let service = BoltServer::builder(backend).into_service();
+    /// let mut session = BoltSession::connect_in_process(&service).await?;
+    /// let result = session.run("RETURN 1 AS n").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn connect_in_process<B: BoltBackend>(
+        service: &BoltService<B>,
+    ) -> Result<Self, BoltError> {
+        let conn = BoltConnection::connect_in_process(service).await?;
+        Self::authenticate(conn, None).await
+    }
+
+    /// Connects to an in-process [`BoltService`] and authenticates with basic auth.
+    pub async fn connect_in_process_basic<B: BoltBackend>(
+        service: &BoltService<B>,
+        username: &str,
+        password: &str,
+    ) -> Result<Self, BoltError> {
+        let conn = BoltConnection::connect_in_process(service).await?;
+        Self::authenticate(conn, Some((username, password))).await
+    }
+
+    /// Sends HELLO and LOGON: basic auth with `credentials`, or the "none"
+    /// scheme without.
+    async fn authenticate(
+        conn: BoltConnection,
+        credentials: Option<(&str, &str)>,
+    ) -> Result<Self, BoltError> {
+        let token = match credentials {
+            Some((username, password)) => AuthToken::basic(username, password),
+            None => AuthToken::none(),
+        };
+        Self::start(conn, token, None).await
+    }
+
+    async fn start(
+        mut conn: BoltConnection,
+        token: AuthToken,
+        token_manager: Option<Arc<dyn AuthTokenManager>>,
+    ) -> Result<Self, BoltError> {
         let extra = BoltDict::from([(
             "user_agent".to_string(),
             BoltValue::String("boltr-client/0.2.0".to_string()),
         )]);
         conn.hello(extra).await?;
-        conn.logon("basic", Some(username), Some(password)).await?;
-        Ok(Self { conn })
+        logon(&mut conn, &token).await?;
+        Ok(Self {
+            conn,
+            token,
+            token_manager,
+            in_transaction: false,
+            bookmarks: Vec::new(),
+            tx_bookmarks: Vec::new(),
+            bookmark_manager: None,
+            retry_policy: RetryPolicy::default(),
+            fetch_size: DEFAULT_FETCH_SIZE,
+            abandoned: None,
+        })
+    }
+
+    /// Sets how many records a [`RecordStream`] PULLs at a time; -1 fetches
+    /// all at once. Default: 1000.
+    #[must_use]
+    pub fn with_fetch_size(mut self, fetch_size: i64) -> Self {
+        self.fetch_size = fetch_size;
+        self
+    }
+
+    /// Sets how long to wait for each reply before the connection counts as
+    /// broken; see [`BoltConnection::set_request_timeout`]. Default: none,
+    /// or the server's `connection.recv_timeout_seconds` hint.
+    #[must_use]
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.conn.set_request_timeout(Some(timeout));
+        self
+    }
+
+    /// Sets how [`execute_read`](Self::execute_read) and
+    /// [`execute_write`](Self::execute_write) retry.
+    #[must_use]
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Shares bookmarks with other sessions through `manager`: each
+    /// transaction waits for the commits of all of them.
+    #[must_use]
+    pub fn with_bookmark_manager(mut self, manager: Arc<dyn BookmarkManager>) -> Self {
+        self.bookmark_manager = Some(manager);
+        self
+    }
+
+    /// Starts the session's causal chain from `bookmarks`, e.g. those of
+    /// another session.
+    #[must_use]
+    pub fn with_bookmarks(mut self, bookmarks: Vec<String>) -> Self {
+        self.bookmarks = bookmarks;
+        self
+    }
+
+    /// Bookmarks of the last commit in this session (or those it started
+    /// with).
+    pub fn last_bookmarks(&self) -> &[String] {
+        &self.bookmarks
+    }
+
+    /// The session's bookmarks plus those of the bookmark manager.
+    async fn bookmarks_to_send(&mut self) -> Vec<String> {
+        let mut bookmarks = self.bookmarks.clone();
+        if let Some(ref manager) = self.bookmark_manager {
+            for bookmark in manager.get_bookmarks().await {
+                if !bookmarks.contains(&bookmark) {
+                    bookmarks.push(bookmark);
+                }
+            }
+        }
+        bookmarks
+    }
+
+    /// Adds the bookmarks to send to `extra`, unless the caller set some;
+    /// returns the bookmarks sent.
+    async fn attach_bookmarks(&mut self, extra: &mut BoltDict) -> Vec<String> {
+        if let Some(BoltValue::List(_)) = extra.get("bookmarks") {
+            return extract_bookmarks(extra);
+        }
+        let bookmarks = self.bookmarks_to_send().await;
+        if !bookmarks.is_empty() {
+            extra.insert(
+                "bookmarks".into(),
+                BoltValue::List(bookmarks.iter().cloned().map(BoltValue::String).collect()),
+            );
+        }
+        bookmarks
+    }
+
+    /// Records the bookmark of a commit that started from `previous`.
+    async fn committed(&mut self, previous: &[String], bookmark: Option<&BoltValue>) {
+        let Some(BoltValue::String(bookmark)) = bookmark else {
+            return;
+        };
+        self.bookmarks = vec![bookmark.clone()];
+        if let Some(ref manager) = self.bookmark_manager {
+            manager.update_bookmarks(previous, &self.bookmarks).await;
+        }
+    }
+
+    /// Whether `e` reports expired credentials that the token manager can
+    /// replace.
+    fn can_reauthenticate(&self, e: &BoltError) -> bool {
+        self.token_manager.is_some()
+            && match e {
+                BoltError::TokenExpired(_) => true,
+                BoltError::Query { code, .. } => code == TOKEN_EXPIRED,
+                _ => false,
+            }
+    }
+
+    /// Logs on again with a fresh token from the token manager.
+    async fn reauthenticate(&mut self) -> Result<(), BoltError> {
+        let Some(manager) = self.token_manager.clone() else {
+            return Err(BoltError::Authentication("no token manager".into()));
+        };
+        manager.on_token_expired(&self.token).await;
+        let token = manager.get_token().await?;
+        self.conn.logoff().await?;
+        logon(&mut self.conn, &token).await?;
+        self.token = token;
+        self.in_transaction = false;
+        Ok(())
     }
 
     /// Returns the negotiated Bolt version.
@@ -117,59 +382,421 @@ impl BoltSession {
         params: HashMap<String, BoltValue>,
         extra: BoltDict,
     ) -> Result<QueryResult, BoltError> {
-        let run_meta = self.conn.run(query, params, extra).await?;
-
-        let columns: Vec<String> = run_meta
-            .get("fields")
-            .and_then(|v| {
-                if let BoltValue::List(items) = v {
-                    Some(
-                        items
-                            .iter()
-                            .filter_map(|item| item.as_str().map(String::from))
-                            .collect(),
-                    )
-                } else {
-                    None
-                }
-            })
-            .unwrap_or_default();
-
-        let (records, summary) = self.conn.pull_all().await?;
-
+        let mut stream = self.open(query, params, extra, -1).await?;
+        let columns = stream.keys().to_vec();
+        let mut records = Vec::new();
+        while let Some(record) = stream.next().await {
+            records.push(record?);
+        }
         Ok(QueryResult {
             columns,
             records,
-            summary,
+            summary: stream.consume().await?,
         })
     }
 
+    /// Runs a query and returns its records as a [`RecordStream`]
+    /// (auto-commit, or in the open transaction).
+    pub async fn run_stream(&mut self, query: &str) -> Result<RecordStream<'_>, BoltError> {
+        self.run_stream_with_params(query, HashMap::new(), BoltDict::new())
+            .await
+    }
+
+    /// Runs a query with parameters and extra metadata and returns its
+    /// records as a [`RecordStream`].
+    pub async fn run_stream_with_params(
+        &mut self,
+        query: &str,
+        params: HashMap<String, BoltValue>,
+        extra: BoltDict,
+    ) -> Result<RecordStream<'_>, BoltError> {
+        let fetch_size = self.fetch_size;
+        self.open(query, params, extra, fetch_size).await
+    }
+
+    /// Sends RUN, logging on again and retrying once if the token expired.
+    async fn open(
+        &mut self,
+        query: &str,
+        params: HashMap<String, BoltValue>,
+        extra: BoltDict,
+        fetch_size: i64,
+    ) -> Result<RecordStream<'_>, BoltError> {
+        self.settle().await?;
+        let (keys, t_first, bookmarks) = if self.token_manager.is_none() {
+            self.start_run(query, params, extra, fetch_size).await?
+        } else {
+            let in_transaction = self.in_transaction;
+            match self
+                .start_run(query, params.clone(), extra.clone(), fetch_size)
+                .await
+            {
+                Err(e) if self.can_reauthenticate(&e) => {
+                    self.reauthenticate().await?;
+                    if in_transaction {
+                        // The server rolled the transaction back.
+                        return Err(e);
+                    }
+                    self.start_run(query, params, extra, fetch_size).await?
+                }
+                result => result?,
+            }
+        };
+        Ok(RecordStream::new(
+            self, true, keys, fetch_size, t_first, bookmarks,
+        ))
+    }
+
+    /// Sends RUN, with the session's bookmarks unless in a transaction, and
+    /// a PULL of `fetch_size` records in the same write. Returns the column
+    /// names, `t_first` and the bookmarks sent.
+    async fn start_run(
+        &mut self,
+        query: &str,
+        params: HashMap<String, BoltValue>,
+        mut extra: BoltDict,
+        fetch_size: i64,
+    ) -> Result<(Vec<String>, Option<BoltValue>, Option<Vec<String>>), BoltError> {
+        let sent = if self.in_transaction {
+            None
+        } else {
+            Some(self.attach_bookmarks(&mut extra).await)
+        };
+        self.conn.queue(&run_message(query, params, extra)).await?;
+        self.conn.queue(&ClientMessage::pull_n(fetch_size)).await?;
+        self.conn.flush().await?;
+        let mut run_meta = match self.conn.recv_summary("RUN").await {
+            Ok(run_meta) => run_meta,
+            Err(e) => {
+                if answered(&e) {
+                    // The PULL is IGNORED.
+                    self.conn.recv().await?;
+                }
+                return Err(e);
+            }
+        };
+        Ok((columns(&run_meta), run_meta.remove("t_first"), sent))
+    }
+
+    /// Sends `request`, a PULL or DISCARD, if given, and receives the reply.
+    /// At the end of an auto-commit result, records its bookmark as a
+    /// commit that started from `bookmarks`.
+    ///
+    /// Until the reply is read, the session notes what is left of it, so
+    /// that a fetch dropped midway is completed by [`settle`](Self::settle).
+    pub(super) async fn fetch(
+        &mut self,
+        request: Option<&ClientMessage>,
+        bookmarks: Option<&[String]>,
+    ) -> Result<(Vec<Vec<BoltValue>>, BoltDict), BoltError> {
+        if let Some(request) = request {
+            self.abandoned = Some(Abandoned::Request);
+            let sent = self.conn.send(request).await;
+            self.abandoned = None;
+            sent?;
+        }
+        // Reads are cancel safe: the rest of the reply can be read later.
+        self.abandoned = Some(Abandoned::Pulled(bookmarks.map(<[_]>::to_vec)));
+        let received = self.conn.recv_records().await;
+        self.abandoned = None;
+        let (records, metadata) = received?;
+        let has_more = matches!(metadata.get("has_more"), Some(BoltValue::Boolean(true)));
+        if let Some(previous) = bookmarks
+            && !has_more
+        {
+            self.committed(previous, metadata.get("bookmark")).await;
+        }
+        Ok((records, metadata))
+    }
+
+    pub(super) fn abandon(&mut self, abandoned: Abandoned) {
+        self.abandoned = Some(abandoned);
+    }
+
+    /// Reads the rest of an abandoned reply and DISCARDs the rest of an
+    /// abandoned result before the next request.
+    async fn settle(&mut self) -> Result<(), BoltError> {
+        match self.abandoned.take() {
+            None => Ok(()),
+            Some(Abandoned::Result(bookmarks)) => {
+                let discard = ClientMessage::discard_all();
+                self.fetch(Some(&discard), bookmarks.as_deref()).await?;
+                Ok(())
+            }
+            Some(Abandoned::Pulled(bookmarks)) => {
+                let (_, metadata) = self.fetch(None, bookmarks.as_deref()).await?;
+                if let Some(BoltValue::Boolean(true)) = metadata.get("has_more") {
+                    let discard = ClientMessage::discard_all();
+                    self.fetch(Some(&discard), bookmarks.as_deref()).await?;
+                }
+                Ok(())
+            }
+            Some(Abandoned::Request) => {
+                self.abandoned = Some(Abandoned::Request);
+                Err(abandoned_request())
+            }
+        }
+    }
+
+    /// Starts a [`Pipeline`] of queries sent in one write.
+    pub fn pipeline(&mut self) -> Pipeline<'_> {
+        Pipeline::new(self)
+    }
+
+    /// Runs a pipeline, logging on again and retrying once if the token
+    /// expired and the pipeline has its own transaction.
+    pub(super) async fn run_pipeline(
+        &mut self,
+        statements: &[Statement],
+    ) -> Result<Vec<QueryResult>, BoltError> {
+        self.settle().await?;
+        let own_transaction = !self.in_transaction;
+        match self.pipeline_once(statements).await {
+            Err(e) if own_transaction && self.can_reauthenticate(&e) => {
+                self.reauthenticate().await?;
+                self.pipeline_once(statements).await
+            }
+            result => result,
+        }
+    }
+
+    async fn pipeline_once(
+        &mut self,
+        statements: &[Statement],
+    ) -> Result<Vec<QueryResult>, BoltError> {
+        let own_transaction = !self.in_transaction;
+        let mut previous = Vec::new();
+        if own_transaction {
+            let mut extra = BoltDict::new();
+            previous = self.attach_bookmarks(&mut extra).await;
+            self.conn.queue(&ClientMessage::Begin { extra }).await?;
+        }
+        for statement in statements {
+            let run = run_message(
+                &statement.query,
+                statement.params.clone(),
+                statement.extra.clone(),
+            );
+            self.conn.queue(&run).await?;
+            self.conn.queue(&ClientMessage::pull_all()).await?;
+        }
+        if own_transaction {
+            self.conn.queue(&ClientMessage::Commit).await?;
+        }
+        self.conn.flush().await?;
+
+        let mut unanswered = 2 * statements.len() + if own_transaction { 2 } else { 0 };
+        match self
+            .read_pipeline(own_transaction, statements.len(), &mut unanswered)
+            .await
+        {
+            Ok((results, commit)) => {
+                self.committed(&previous, commit.get("bookmark")).await;
+                Ok(results)
+            }
+            Err(e) => {
+                if answered(&e) {
+                    // Every request after the failed one is IGNORED.
+                    for _ in 0..unanswered {
+                        self.conn.recv().await?;
+                    }
+                    if own_transaction {
+                        self.conn.reset().await?;
+                    }
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Reads the replies to a pipeline of `queries` RUN and PULL pairs,
+    /// between BEGIN and COMMIT if `own_transaction`, counting down
+    /// `unanswered`. Returns the results and the COMMIT metadata.
+    async fn read_pipeline(
+        &mut self,
+        own_transaction: bool,
+        queries: usize,
+        unanswered: &mut usize,
+    ) -> Result<(Vec<QueryResult>, BoltDict), BoltError> {
+        if own_transaction {
+            *unanswered -= 1;
+            self.conn.recv_summary("BEGIN").await?;
+        }
+        let mut results = Vec::with_capacity(queries);
+        for _ in 0..queries {
+            *unanswered -= 1;
+            let run_meta = self.conn.recv_summary("RUN").await?;
+            *unanswered -= 1;
+            let (records, summary) = self.conn.recv_records().await?;
+            results.push(query_result(run_meta, records, summary));
+        }
+        let mut commit = BoltDict::new();
+        if own_transaction {
+            *unanswered -= 1;
+            commit = self.conn.recv_summary("COMMIT").await?;
+        }
+        Ok((results, commit))
+    }
+
     /// Begins an explicit transaction.
     pub async fn begin(&mut self) -> Result<(), BoltError> {
-        self.conn.begin(BoltDict::new()).await
+        self.begin_with(BoltDict::new()).await
+    }
+
+    /// Begins an explicit transaction with BEGIN `extra` fields such as
+    /// `db`, `mode` or `tx_timeout`. The session's bookmarks are added
+    /// unless `extra` has `bookmarks`.
+    pub async fn begin_with(&mut self, mut extra: BoltDict) -> Result<(), BoltError> {
+        self.settle().await?;
+        self.tx_bookmarks = self.attach_bookmarks(&mut extra).await;
+        match self.conn.begin(extra.clone()).await {
+            Err(e) if self.can_reauthenticate(&e) => {
+                self.reauthenticate().await?;
+                self.conn.begin(extra).await?;
+            }
+            result => result?,
+        }
+        self.in_transaction = true;
+        Ok(())
     }
 
     /// Commits the current transaction. Returns SUCCESS metadata
-    /// which may contain a `"bookmark"` for causal consistency.
+    /// which may contain a `"bookmark"`; the session keeps it for the
+    /// next transaction and reports it to the bookmark manager.
     pub async fn commit(&mut self) -> Result<BoltDict, BoltError> {
-        self.conn.commit().await
+        self.settle().await?;
+        self.in_transaction = false;
+        let metadata = self.conn.commit().await?;
+        let previous = std::mem::take(&mut self.tx_bookmarks);
+        self.committed(&previous, metadata.get("bookmark")).await;
+        Ok(metadata)
+    }
+
+    /// Runs `work` in a read transaction and commits it. See
+    /// [`execute_write`](Self::execute_write).
+    pub async fn execute_read<T, F>(&mut self, work: F) -> Result<T, BoltError>
+    where
+        F: AsyncFnMut(&mut ManagedTransaction<'_>) -> Result<T, BoltError>,
+    {
+        let extra = BoltDict::from([("mode".to_string(), BoltValue::String("r".into()))]);
+        self.execute(extra, work).await
+    }
+
+    /// Runs `work` in a write transaction and commits it.
+    ///
+    /// If `work`, BEGIN or COMMIT fails, the transaction is rolled back. A
+    /// [retryable](BoltError::is_retryable) failure runs `work` again in a
+    /// new transaction, after a back-off set by the
+    /// [`RetryPolicy`]; any other failure, including a lost connection, is
+    /// returned.
+    ///
+    /// ```rust,no_run
+    /// # async fn example() -> Result<(), boltr::error::BoltError> {
+    /// use boltr::client::BoltSession;
+    ///
+    /// let mut session = BoltSession::connect("127.0.0.1:7687".parse().unwrap()).await?;
+    /// let created = session
+    ///     .execute_write(async |tx| {
+    ///         let result = tx.run("CREATE (n:Test) RETURN n").await?;
+    ///         Ok(result.summary.counters.nodes_created)
+    ///     })
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn execute_write<T, F>(&mut self, work: F) -> Result<T, BoltError>
+    where
+        F: AsyncFnMut(&mut ManagedTransaction<'_>) -> Result<T, BoltError>,
+    {
+        self.execute(BoltDict::new(), work).await
+    }
+
+    async fn execute<T, F>(&mut self, extra: BoltDict, mut work: F) -> Result<T, BoltError>
+    where
+        F: AsyncFnMut(&mut ManagedTransaction<'_>) -> Result<T, BoltError>,
+    {
+        let started = Instant::now();
+        let mut retry = 0;
+        loop {
+            let e = match self.attempt(extra.clone(), &mut work).await {
+                Ok(value) => return Ok(value),
+                Err(e) => e,
+            };
+            // A credential refresh has rolled the transaction back already.
+            let retryable = e.is_retryable() || self.can_reauthenticate(&e);
+            if self.roll_back_attempt().await.is_err() || !retryable {
+                return Err(e);
+            }
+            let Some(delay) = self.retry_policy.delay(retry, started.elapsed()) else {
+                return Err(e);
+            };
+            tracing::debug!(error = %e, ?delay, "retrying transaction");
+            tokio::time::sleep(delay).await;
+            retry += 1;
+        }
+    }
+
+    async fn attempt<T, F>(&mut self, extra: BoltDict, work: &mut F) -> Result<T, BoltError>
+    where
+        F: AsyncFnMut(&mut ManagedTransaction<'_>) -> Result<T, BoltError>,
+    {
+        self.begin_with(extra).await?;
+        let value = work(&mut ManagedTransaction { session: self }).await?;
+        self.commit().await?;
+        Ok(value)
+    }
+
+    /// Ends a failed attempt: ROLLBACK, or RESET when the server has
+    /// already failed the transaction.
+    async fn roll_back_attempt(&mut self) -> Result<(), BoltError> {
+        if self.rollback().await.is_err() {
+            self.reset().await?;
+        }
+        Ok(())
     }
 
     /// Rolls back the current transaction.
     pub async fn rollback(&mut self) -> Result<BoltDict, BoltError> {
+        self.settle().await?;
+        self.in_transaction = false;
         self.conn.rollback().await
     }
 
     /// Discards all remaining records from the current result stream.
     pub async fn discard(&mut self) -> Result<(), BoltError> {
-        self.conn.discard_all().await
+        self.conn.discard_all().await.map(drop)
     }
 
     /// Resets the connection to a clean state.
     pub async fn reset(&mut self) -> Result<(), BoltError> {
+        match self.abandoned.take() {
+            // RESET discards an abandoned result, but not a partly sent request.
+            Some(Abandoned::Request) => {
+                self.abandoned = Some(Abandoned::Request);
+                return Err(abandoned_request());
+            }
+            // The rest of an abandoned reply comes before RESET's. The RESET
+            // clears a FAILURE in it and fails on a broken connection.
+            Some(Abandoned::Pulled(_)) => {
+                let _ = self.fetch(None, None).await;
+            }
+            Some(Abandoned::Result(_)) | None => {}
+        }
+        self.in_transaction = false;
         self.conn.reset().await
     }
 
+    /// Fetches the routing table for `db`, or for the home database.
+    pub async fn route(&mut self, db: Option<&str>) -> Result<RoutingTable, BoltError> {
+        self.settle().await?;
+        let mut extra = BoltDict::new();
+        if let Some(db) = db {
+            extra.insert("db".into(), BoltValue::String(db.into()));
+        }
+        let bookmarks = self.bookmarks_to_send().await;
+        self.conn.route(BoltDict::new(), bookmarks, extra).await
+    }
+
     /// Sends GOODBYE (graceful disconnect).
     pub async fn close(mut self) -> Result<(), BoltError> {
         self.conn.goodbye().await
@@ -182,14 +809,517 @@ impl BoltSession {
     }
 }
 
+fn run_message(
+    query: &str,
+    parameters: HashMap<String, BoltValue>,
+    extra: BoltDict,
+) -> ClientMessage {
+    ClientMessage::Run {
+        query: query.to_string(),
+        parameters,
+        extra,
+    }
+}
+
+/// Builds the result of a RUN and a PULL of all records.
+fn query_result(
+    mut run_meta: BoltDict,
+    records: Vec<Vec<BoltValue>>,
+    mut summary: BoltDict,
+) -> QueryResult {
+    let columns = columns(&run_meta);
+    if let Some(t_first) = run_meta.remove("t_first") {
+        summary.entry("t_first".into()).or_insert(t_first);
+    }
+    let keys: Arc<[String]> = columns.clone().into();
+    QueryResult {
+        columns,
+        records: records
+            .into_iter()
+            .map(|values| Record::new(keys.clone(), values))
+            .collect(),
+        summary: ResultSummary::from_metadata(&summary),
+    }
+}
+
+/// Column names from RUN SUCCESS metadata.
+fn columns(run_meta: &BoltDict) -> Vec<String> {
+    run_meta
+        .get("fields")
+        .and_then(|v| {
+            if let BoltValue::List(items) = v {
+                Some(
+                    items
+                        .iter()
+                        .filter_map(|item| item.as_str().map(String::from))
+                        .collect(),
+                )
+            } else {
+                None
+            }
+        })
+        .unwrap_or_default()
+}
+
+/// Whether the server replied to the failed request, with FAILURE or
+/// IGNORED, so that it goes on to answer the requests sent after it.
+fn answered(e: &BoltError) -> bool {
+    !matches!(e, BoltError::Io(_))
+}
+
+fn abandoned_request() -> BoltError {
+    BoltError::Protocol(
+        "a record stream was dropped while sending a request; reconnect the session".into(),
+    )
+}
+
+async fn logon(conn: &mut BoltConnection, token: &AuthToken) -> Result<(), BoltError> {
+    conn.logon(
+        &token.scheme,
+        token.principal.as_deref(),
+        token.credentials.as_deref(),
+    )
+    .await
+}
+
+/// The transaction a [`BoltSession::execute_read`] or
+/// [`BoltSession::execute_write`] function runs its queries in. The session
+/// commits or rolls it back.
+pub struct ManagedTransaction<'a> {
+    session: &'a mut BoltSession,
+}
+
+impl ManagedTransaction<'_> {
+    /// Runs a query in the transaction.
+    pub async fn run(&mut self, query: &str) -> Result<QueryResult, BoltError> {
+        self.session.run(query).await
+    }
+
+    /// Runs a query with parameters and extra metadata in the transaction.
+    pub async fn run_with_params(
+        &mut self,
+        query: &str,
+        params: HashMap<String, BoltValue>,
+        extra: BoltDict,
+    ) -> Result<QueryResult, BoltError> {
+        self.session.run_with_params(query, params, extra).await
+    }
+}
+
+/// Records per PULL of a [`RecordStream`] by default.
+const DEFAULT_FETCH_SIZE: i64 = 1000;
+
 /// Result of a Bolt query execution.
 #[derive(Debug)]
 #[must_use]
 pub struct QueryResult {
     /// Column names from the RUN metadata.
     pub columns: Vec<String>,
-    /// Records (rows), each a list of `BoltValue`.
-    pub records: Vec<Vec<BoltValue>>,
-    /// Summary metadata from the final PULL SUCCESS.
-    pub summary: BoltDict,
+    /// Records (rows), which share the column names.
+    pub records: Vec<Record>,
+    /// Summary from the final PULL SUCCESS, with `t_first` from the RUN
+    /// SUCCESS.
+    pub summary: ResultSummary,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::SystemTime;
+
+    use crate::types::QueryType;
+
+    use crate::client::InMemoryBookmarkManager;
+    use crate::server::BookmarkManager as ServerBookmarks;
+    use crate::server::test_support::MockBackend;
+    use crate::server::{AuthCredentials, AuthInfo, AuthValidator, BoltServer};
+
+    #[tokio::test]
+    async fn in_process_session_runs_queries() {
+        let backend = MockBackend::default();
+        let service = BoltServer::builder(backend.clone()).into_service();
+
+        let mut session = BoltSession::connect_in_process(&service).await.unwrap();
+        let result = session.run("RETURN 1 AS n").await.unwrap();
+        assert_eq!(result.columns, vec!["n"]);
+        assert_eq!(result.records[0].values(), [BoltValue::Integer(1)]);
+        assert_eq!(result.records[0].get_typed::<i64>("n").unwrap(), 1);
+        assert!(result.records[0].get_typed::<String>("n").is_err());
+        assert_eq!(
+            result.records[0].clone().into_map(),
+            BoltDict::from([("n".to_string(), BoltValue::Integer(1))])
+        );
+
+        session.begin().await.unwrap();
+        let created = session.run("CREATE ()").await.unwrap();
+        assert_eq!(created.records.len(), 1);
+        assert_eq!(created.summary.query_type, Some(QueryType::Write));
+        assert_eq!(created.summary.counters.nodes_created, 1);
+        assert!(created.summary.result_consumed_after.is_some());
+        session.commit().await.unwrap();
+        session.close().await.unwrap();
+
+        // GOODBYE ends the server side, which closes the session.
+        let closed = async {
+            while service.session_manager().count() > 0 {
+                tokio::task::yield_now().await;
+            }
+        };
+        tokio::time::timeout(std::time::Duration::from_secs(5), closed)
+            .await
+            .unwrap();
+        assert_eq!(*backend.closed.lock().unwrap(), vec!["s0"]);
+    }
+
+    #[tokio::test]
+    async fn bookmarks_chain_across_sessions() {
+        let server_bookmarks =
+            Arc::new(ServerBookmarks::new().wait_timeout(Duration::from_millis(100)));
+        let service = BoltServer::builder(MockBackend::default())
+            .bookmark_manager(server_bookmarks.clone())
+            .into_service();
+        let shared = Arc::new(InMemoryBookmarkManager::default());
+
+        let mut writer = BoltSession::connect_in_process(&service)
+            .await
+            .unwrap()
+            .with_bookmark_manager(shared.clone());
+        let _ = writer.run("CREATE ()").await.unwrap();
+        assert_eq!(writer.last_bookmarks(), ["bk:neo4j:1"]);
+        writer.begin().await.unwrap();
+        let _ = writer.run("CREATE ()").await.unwrap();
+        writer.commit().await.unwrap();
+        assert_eq!(writer.last_bookmarks(), ["bk:neo4j:2"]);
+        // The second commit superseded the first.
+        assert_eq!(shared.get_bookmarks().await, ["bk:neo4j:2"]);
+
+        let mut reader = BoltSession::connect_in_process(&service)
+            .await
+            .unwrap()
+            .with_bookmark_manager(shared.clone());
+        let _ = reader.run("RETURN 1").await.unwrap();
+        assert_eq!(reader.last_bookmarks(), ["bk:neo4j:2"]);
+        let table = reader.route(Some("neo4j")).await.unwrap();
+        assert_eq!(table.db, "neo4j");
+
+        // A bookmark the server has not reached yet times out.
+        let mut ahead = BoltSession::connect_in_process(&service)
+            .await
+            .unwrap()
+            .with_bookmarks(vec!["bk:neo4j:9".into()]);
+        assert!(ahead.run("RETURN 1").await.is_err());
+        server_bookmarks.advance("neo4j", 9);
+        ahead.reset().await.unwrap();
+        let _ = ahead.run("RETURN 1").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn managed_transactions_retry_transient_failures() {
+        let backend = MockBackend::default();
+        let service = BoltServer::builder(backend.clone()).into_service();
+        let policy = RetryPolicy::new()
+            .initial_delay(Duration::from_millis(10))
+            .max_retry_time(Duration::from_millis(500));
+        let mut session = BoltSession::connect_in_process(&service)
+            .await
+            .unwrap()
+            .with_retry_policy(policy);
+
+        backend.transient_failures.store(2, Ordering::Relaxed);
+        let mut attempts = 0;
+        let created = session
+            .execute_write(async |tx| {
+                attempts += 1;
+                Ok(tx.run("CREATE ()").await?.summary.counters.nodes_created)
+            })
+            .await
+            .unwrap();
+        assert_eq!((created, attempts), (1, 3));
+
+        // Other errors roll back without a retry.
+        let rolled_back = backend.rolled_back.lock().unwrap().len();
+        let failed = session
+            .execute_read(async |tx| {
+                let _ = tx.run("RETURN 1").await?;
+                Err::<(), _>(BoltError::Backend("not found".into()))
+            })
+            .await;
+        assert!(matches!(failed, Err(BoltError::Backend(_))));
+        assert_eq!(backend.rolled_back.lock().unwrap().len(), rolled_back + 1);
+
+        // Retries stop at the deadline.
+        backend
+            .transient_failures
+            .store(u64::MAX, Ordering::Relaxed);
+        let exhausted = session
+            .execute_write(async |tx| tx.run("RETURN 1").await.map(drop))
+            .await;
+        assert!(exhausted.unwrap_err().is_retryable());
+        backend.transient_failures.store(0, Ordering::Relaxed);
+        let _ = session.run("RETURN 1").await.unwrap();
+    }
+
+    /// Sessions must be usable from spawned tasks.
+    #[allow(dead_code)]
+    fn session_futures_are_send(session: &mut BoltSession) {
+        fn assert_send<T: Send>(_: T) {}
+        assert_send(async {
+            let _ = session.run("RETURN 1").await;
+            let _ = session.begin().await;
+            let _ = session.commit().await;
+            let _ = session.route(None).await;
+            let _ = session.execute_write(async |_| Ok(())).await;
+            let _ = session.pipeline().run("RETURN 1").execute().await;
+            let mut stream = session.run_stream("RETURN 1").await.unwrap();
+            let _ = stream.next().await;
+            let _ = stream.consume().await;
+        });
+    }
+
+    #[tokio::test]
+    async fn record_streams_fetch_lazily() {
+        let service = BoltServer::builder(MockBackend::default()).into_service();
+        let mut session = BoltSession::connect_in_process(&service)
+            .await
+            .unwrap()
+            .with_fetch_size(2);
+
+        let mut stream = session.run_stream("RANGE 5").await.unwrap();
+        assert_eq!(stream.keys(), ["n"]);
+        let mut values = Vec::new();
+        while let Some(record) = stream.next().await {
+            values.push(record.unwrap().get("n").unwrap().clone());
+        }
+        assert_eq!(values, (1..=5).map(BoltValue::Integer).collect::<Vec<_>>());
+        assert!(stream.summary().is_some());
+        let summary = stream.consume().await.unwrap();
+        assert_eq!(summary.query_type, Some(QueryType::Read));
+
+        // A stream dropped midway is discarded before the next request.
+        let mut stream = session.run_stream("RANGE 5").await.unwrap();
+        let first = stream.next().await.unwrap().unwrap();
+        assert_eq!(
+            first.iter().collect::<Vec<_>>(),
+            [("n", &BoltValue::Integer(1))]
+        );
+        drop(stream);
+        let result = session.run("RETURN 1").await.unwrap();
+        assert_eq!(result.records[0].values(), [BoltValue::Integer(1)]);
+
+        // consume discards what is left.
+        let stream = session.run_stream("RANGE 5").await.unwrap();
+        assert!(
+            stream
+                .consume()
+                .await
+                .unwrap()
+                .result_consumed_after
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn fetches_cancelled_mid_batch_are_completed() {
+        let service = BoltServer::builder(MockBackend::default()).into_service();
+        let mut session = BoltSession::connect_in_process(&service)
+            .await
+            .unwrap()
+            .with_fetch_size(15_000);
+        // A batch larger than the in-process buffer arrives in parts, so
+        // polling `next()` once leaves it partly read.
+        async fn cancel_mid_batch(stream: &mut RecordStream<'_>) {
+            for _ in 0..10 {
+                tokio::task::yield_now().await;
+            }
+            assert!(
+                tokio::time::timeout(Duration::ZERO, stream.next())
+                    .await
+                    .is_err()
+            );
+        }
+
+        // The stream goes on where the cancelled call stopped.
+        let mut stream = session.run_stream("RANGE 20000").await.unwrap();
+        cancel_mid_batch(&mut stream).await;
+        let mut count = 0;
+        while let Some(record) = stream.next().await {
+            count += 1;
+            assert_eq!(record.unwrap().values(), [BoltValue::Integer(count)]);
+        }
+        assert_eq!(count, 20_000);
+        drop(stream);
+
+        // A stream dropped mid-batch is read to the end of the batch and
+        // DISCARDed before the next request, or before a RESET.
+        let mut stream = session.run_stream("RANGE 20000").await.unwrap();
+        cancel_mid_batch(&mut stream).await;
+        drop(stream);
+        let result = session.run("RETURN 1").await.unwrap();
+        assert_eq!(result.records[0].values(), [BoltValue::Integer(1)]);
+
+        let mut stream = session.run_stream("RANGE 20000").await.unwrap();
+        cancel_mid_batch(&mut stream).await;
+        drop(stream);
+        session.reset().await.unwrap();
+        let result = session.run("RETURN 1").await.unwrap();
+        assert_eq!(result.records[0].values(), [BoltValue::Integer(1)]);
+    }
+
+    #[tokio::test]
+    async fn pipelines_run_in_one_transaction() {
+        let backend = MockBackend::default();
+        let service = BoltServer::builder(backend.clone())
+            .bookmark_manager(Arc::new(ServerBookmarks::new()))
+            .into_service();
+        let mut session = BoltSession::connect_in_process(&service).await.unwrap();
+
+        let results = session
+            .pipeline()
+            .run("CREATE ()")
+            .run("RANGE 3")
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(results[0].summary.counters.nodes_created, 1);
+        assert_eq!(results[1].records.len(), 3);
+        assert_eq!(session.last_bookmarks(), ["bk:neo4j:1"]);
+
+        // A failure rolls the transaction back and leaves the session usable.
+        backend.transient_failures.store(1, Ordering::Relaxed);
+        let failed = session
+            .pipeline()
+            .run("CREATE ()")
+            .run("RETURN 1")
+            .execute()
+            .await;
+        assert!(failed.unwrap_err().is_retryable());
+        assert_eq!(backend.rolled_back.lock().unwrap().len(), 1);
+        assert_eq!(session.last_bookmarks(), ["bk:neo4j:1"]);
+
+        // In an explicit transaction, only the queries are pipelined.
+        session.begin().await.unwrap();
+        let results = session.pipeline().run("RETURN 1").execute().await.unwrap();
+        assert_eq!(results[0].records[0].values(), [BoltValue::Integer(1)]);
+        session.commit().await.unwrap();
+
+        // A failed auto-commit RUN consumes the IGNORED PULL sent with it.
+        backend.transient_failures.store(1, Ordering::Relaxed);
+        assert!(session.run("RETURN 1").await.is_err());
+        session.reset().await.unwrap();
+        let _ = session.run("RETURN 1").await.unwrap();
+    }
+
+    /// Accepts any bearer token; `short` expires after 300ms.
+    struct Expiring;
+
+    #[async_trait::async_trait]
+    impl AuthValidator for Expiring {
+        async fn validate(&self, credentials: &AuthCredentials) -> Result<AuthInfo, BoltError> {
+            let token = credentials.credentials.clone().unwrap_or_default();
+            let lifetime = if token == "short" { 300 } else { 3_600_000 };
+            Ok(AuthInfo {
+                principal: token,
+                expires_at: Some(SystemTime::now() + Duration::from_millis(lifetime)),
+                ..AuthInfo::default()
+            })
+        }
+    }
+
+    #[derive(Default)]
+    struct Refreshing {
+        expired: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl AuthTokenManager for Refreshing {
+        async fn get_token(&self) -> Result<AuthToken, BoltError> {
+            Ok(match self.expired.load(Ordering::SeqCst) {
+                0 => AuthToken::bearer("short"),
+                _ => AuthToken::bearer("long"),
+            })
+        }
+
+        async fn on_token_expired(&self, token: &AuthToken) {
+            assert_eq!(token, &AuthToken::bearer("short"));
+            self.expired.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn expired_token_is_refreshed_transparently() {
+        let service = BoltServer::builder(MockBackend::default())
+            .auth(Expiring)
+            .into_service();
+        let manager = Arc::new(Refreshing::default());
+        let conn = BoltConnection::connect_in_process(&service).await.unwrap();
+        let mut session = BoltSession::with_auth(conn, manager.clone()).await.unwrap();
+
+        assert!(session.run("RETURN 1").await.is_ok());
+        tokio::time::advance(Duration::from_millis(400)).await;
+        assert!(session.run("RETURN 1").await.is_ok());
+        assert_eq!(manager.expired.load(Ordering::SeqCst), 1);
+
+        // Without a token manager the failure reaches the caller.
+        let conn = BoltConnection::connect_in_process(&service).await.unwrap();
+        let mut session = BoltSession::with_auth(conn, Arc::new(AuthToken::bearer("short")))
+            .await
+            .unwrap();
+        tokio::time::advance(Duration::from_millis(400)).await;
+        session.token_manager = None;
+        match session.run("RETURN 1").await {
+            Err(BoltError::Query { code, .. }) => assert_eq!(code, TOKEN_EXPIRED),
+            other => panic!("expected TokenExpired, got {other:?}"),
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn timeouts_break_silent_connections() {
+        // The server hints a one second receive timeout and keeps the
+        // connection alive with NOOPs while a slower query runs, or while
+        // BEGIN waits for a bookmark.
+        let bookmarks = Arc::new(ServerBookmarks::new());
+        bookmarks.advance("neo4j", 1);
+        let service = BoltServer::builder(MockBackend::default())
+            .connection_recv_timeout(Duration::from_secs(1))
+            .bookmark_manager(bookmarks.clone())
+            .into_service();
+        let mut session = BoltSession::connect_in_process(&service).await.unwrap();
+        let result = session.run("SLEEP 1300").await.unwrap();
+        assert_eq!(result.records.len(), 1);
+
+        let mut session = BoltSession::connect_in_process(&service)
+            .await
+            .unwrap()
+            .with_bookmarks(vec!["bk:neo4j:2".into()]);
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(1500)).await;
+            bookmarks.advance("neo4j", 2);
+        });
+        session.begin().await.unwrap();
+        session.rollback().await.unwrap();
+
+        let service = BoltServer::builder(MockBackend::default()).into_service();
+        let mut session = BoltSession::connect_in_process(&service)
+            .await
+            .unwrap()
+            .with_request_timeout(Duration::from_millis(100));
+        match session.run("SLEEP 500").await {
+            Err(e @ BoltError::Io(_)) => assert!(!e.is_retryable()),
+            other => panic!("expected a timeout, got {other:?}"),
+        }
+        // The connection is broken from then on.
+        assert!(matches!(
+            session.run("RETURN 1").await,
+            Err(BoltError::Io(_))
+        ));
+
+        // A peer that never answers the handshake.
+        let (client, _server) = tokio::io::duplex(64);
+        let timeouts = Timeouts::new().handshake(Duration::from_millis(50));
+        match BoltConnection::from_stream_with_timeouts(client, &timeouts).await {
+            Err(BoltError::Io(e)) => assert_eq!(e.kind(), std::io::ErrorKind::TimedOut),
+            other => panic!("expected a timeout, got {:?}", other.map(|_| ())),
+        }
+    }
 }
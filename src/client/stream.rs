@@ -0,0 +1,204 @@
+//! Lazily fetched query results.
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, ready};
+
+use futures_core::Stream;
+
+use crate::error::BoltError;
+use crate::message::request::ClientMessage;
+use crate::types::{BoltDict, BoltValue, ResultSummary};
+
+use super::record::Record;
+use super::session::{Abandoned, BoltSession};
+
+type Batch = (Vec<Vec<BoltValue>>, BoltDict);
+
+/// A PULL or DISCARD in flight, which hands the session back when done.
+type Fetch<'a> =
+    Pin<Box<dyn Future<Output = (&'a mut BoltSession, Result<Batch, BoltError>)> + Send + 'a>>;
+
+/// The records of a query, PULLed from the server in batches of the
+/// session's [fetch size](BoltSession::with_fetch_size) as they are
+/// consumed.
+///
+/// The column names are known up front; the summary once the last record
+/// has been read. A stream dropped before its end leaves the rest of the
+/// result to be DISCARDed before the session's next request.
+///
+/// ```rust,no_run
+/// # async fn example() -> Result<(), boltr::error::BoltError> {
+/// use boltr::client::BoltSession;
+///
+/// let mut session = BoltSession::connect("127.0.0.1:7687".parse().unwrap()).await?;
+/// let mut stream = session.run_stream("MATCH (n) RETURN n.name AS name").await?;
+/// println!("columns: {:?}", stream.keys());
+/// while let Some(record) = stream.next().await {
+///     println!("{:?}", record?);
+/// }
+/// let summary = stream.consume().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[must_use = "streams do nothing unless polled"]
+pub struct RecordStream<'a> {
+    state: State<'a>,
+    keys: Arc<[String]>,
+    fetch_size: i64,
+    buffered: VecDeque<Vec<BoltValue>>,
+    /// `t_first` from the RUN SUCCESS.
+    t_first: Option<BoltValue>,
+    /// Bookmarks sent with an auto-commit RUN; the final bookmark
+    /// supersedes them.
+    bookmarks: Option<Vec<String>>,
+    summary: Option<ResultSummary>,
+}
+
+enum State<'a> {
+    /// More records wait on the server.
+    Idle(&'a mut BoltSession),
+    /// A PULL was sent with the RUN; its reply is unread.
+    Sent(&'a mut BoltSession),
+    Fetching(Fetch<'a>),
+    /// The result is complete or failed.
+    Done,
+}
+
+impl<'a> RecordStream<'a> {
+    /// A stream over the result of a RUN sent by `session`, followed by a
+    /// PULL if `pulled`.
+    pub(super) fn new(
+        session: &'a mut BoltSession,
+        pulled: bool,
+        keys: Vec<String>,
+        fetch_size: i64,
+        t_first: Option<BoltValue>,
+        bookmarks: Option<Vec<String>>,
+    ) -> Self {
+        Self {
+            state: if pulled {
+                State::Sent(session)
+            } else {
+                State::Idle(session)
+            },
+            keys: keys.into(),
+            fetch_size,
+            buffered: VecDeque::new(),
+            t_first,
+            bookmarks,
+            summary: None,
+        }
+    }
+
+    /// Column names from the RUN metadata.
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    /// The summary, once every record has been read.
+    pub fn summary(&self) -> Option<&ResultSummary> {
+        self.summary.as_ref()
+    }
+
+    /// Returns the next record, fetching a batch when none are buffered.
+    pub async fn next(&mut self) -> Option<Result<Record, BoltError>> {
+        std::future::poll_fn(|cx| Pin::new(&mut *self).poll_next(cx)).await
+    }
+
+    /// DISCARDs the records not read yet and returns the summary.
+    pub async fn consume(mut self) -> Result<ResultSummary, BoltError> {
+        loop {
+            self.buffered.clear();
+            match self.state {
+                State::Idle(_) | State::Sent(_) => self.start_fetch(ClientMessage::discard_all()),
+                State::Fetching(_) => std::future::poll_fn(|cx| self.poll_fetch(cx)).await?,
+                State::Done => break,
+            }
+        }
+        self.summary
+            .take()
+            .ok_or_else(|| BoltError::Protocol("the result failed".into()))
+    }
+
+    /// Sends `request`, a PULL or DISCARD, or reads the reply to the PULL
+    /// sent with the RUN.
+    fn start_fetch(&mut self, request: ClientMessage) {
+        self.state = match std::mem::replace(&mut self.state, State::Done) {
+            State::Idle(session) => {
+                State::Fetching(fetch(session, Some(request), self.bookmarks.clone()))
+            }
+            State::Sent(session) => State::Fetching(fetch(session, None, self.bookmarks.clone())),
+            state => state,
+        };
+    }
+
+    /// Drives a fetch in flight to completion.
+    fn poll_fetch(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), BoltError>> {
+        let State::Fetching(ref mut fetch) = self.state else {
+            return Poll::Ready(Ok(()));
+        };
+        let (session, result) = ready!(fetch.as_mut().poll(cx));
+        self.state = State::Done;
+        let (records, metadata) = result?;
+        self.buffered.extend(records);
+        if matches!(metadata.get("has_more"), Some(BoltValue::Boolean(true))) {
+            self.state = State::Idle(session);
+        } else {
+            let mut metadata = metadata;
+            if let Some(t_first) = self.t_first.take() {
+                metadata.entry("t_first".into()).or_insert(t_first);
+            }
+            self.summary = Some(ResultSummary::from_metadata(&metadata));
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Stream for RecordStream<'_> {
+    type Item = Result<Record, BoltError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(values) = this.buffered.pop_front() {
+                return Poll::Ready(Some(Ok(Record::new(this.keys.clone(), values))));
+            }
+            match this.state {
+                State::Idle(_) | State::Sent(_) => {
+                    this.start_fetch(ClientMessage::pull_n(this.fetch_size));
+                }
+                State::Fetching(_) => {
+                    if let Err(e) = ready!(this.poll_fetch(cx)) {
+                        return Poll::Ready(Some(Err(e)));
+                    }
+                }
+                State::Done => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+impl Drop for RecordStream<'_> {
+    fn drop(&mut self) {
+        match &mut self.state {
+            State::Idle(session) => session.abandon(Abandoned::Result(self.bookmarks.take())),
+            State::Sent(session) => session.abandon(Abandoned::Pulled(self.bookmarks.take())),
+            State::Fetching(_) | State::Done => {}
+        }
+    }
+}
+
+fn fetch(
+    session: &mut BoltSession,
+    request: Option<ClientMessage>,
+    bookmarks: Option<Vec<String>>,
+) -> Fetch<'_> {
+    // A fetch dropped midway leaves the session to complete it.
+    Box::pin(async move {
+        let result = session.fetch(request.as_ref(), bookmarks.as_deref()).await;
+        (session, result)
+    })
+}
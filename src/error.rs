@@ -32,6 +32,45 @@ pub enum BoltError {
     #[error("forbidden: {0}")]
     Forbidden(String),
 
+    /// The credentials of an authenticated connection have expired; the
+    /// client must re-authenticate with LOGOFF and LOGON.
+    #[error("token expired: {0}")]
+    TokenExpired(String),
+
+    /// A write was attempted in a read-only transaction or session.
+    #[error("access mode: {0}")]
+    AccessMode(String),
+
+    /// A per-IP or per-principal session cap is reached; the client may
+    /// retry once other sessions close.
+    #[error("session limit: {0}")]
+    SessionLimit(String),
+
+    /// Request rate limit exceeded; the client may retry later.
+    #[error("rate limited: {0}")]
+    RateLimited(String),
+
+    /// Too many failed authentication attempts; LOGON is locked for a while.
+    #[error("authentication rate limit: {0}")]
+    AuthenticationRateLimit(String),
+
+    /// A bookmark sent by the client could not be parsed.
+    #[error("invalid bookmark: {0}")]
+    InvalidBookmark(String),
+
+    /// The commits named by the client's bookmarks did not become visible
+    /// in time; the client may retry.
+    #[error("bookmark timeout: {0}")]
+    BookmarkTimeout(String),
+
+    /// The requested database does not exist.
+    #[error("database not found: {0}")]
+    DatabaseNotFound(String),
+
+    /// A value does not have the type it was read as.
+    #[error("conversion error: {0}")]
+    Conversion(String),
+
     #[error("backend error: {0}")]
     Backend(String),
 
@@ -53,6 +92,39 @@ impl BoltError {
         Self::Backend(e.to_string())
     }
 
+    /// Whether the operation that failed may succeed if retried in a new
+    /// transaction: transient server errors (`Neo.TransientError.*` other
+    /// than client-initiated terminations) and leader switches. Lost
+    /// connections are not: the transaction cannot be retried on them, only
+    /// on a new connection.
+    ///
+    /// ```
+    /// use boltr::error::BoltError;
+    ///
+    /// let deadlock = BoltError::Query {
+    ///     code: "Neo.TransientError.Transaction.DeadlockDetected".into(),
+    ///     message: "deadlock".into(),
+    /// };
+    /// assert!(deadlock.is_retryable());
+    /// assert!(!BoltError::Forbidden("no".into()).is_retryable());
+    /// ```
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Query { code, .. } => match code.as_str() {
+                "Neo.TransientError.Transaction.Terminated"
+                | "Neo.TransientError.Transaction.LockClientStopped" => false,
+                "Neo.ClientError.Cluster.NotALeader"
+                | "Neo.ClientError.General.ForbiddenOnReadOnlyDatabase" => true,
+                code => code.starts_with("Neo.TransientError."),
+            },
+            Self::ResourceExhausted(_)
+            | Self::SessionLimit(_)
+            | Self::RateLimited(_)
+            | Self::BookmarkTimeout(_) => true,
+            _ => false,
+        }
+    }
+
     /// Converts this error into a Bolt FAILURE metadata dictionary.
     ///
     /// Returns a map with `"code"` and `"message"` keys matching the
@@ -93,6 +165,21 @@ impl BoltError {
                 e.to_string(),
             ),
             Self::Forbidden(m) => ("Neo.ClientError.Security.Forbidden", m.clone()),
+            Self::TokenExpired(m) => ("Neo.ClientError.Security.TokenExpired", m.clone()),
+            Self::AccessMode(m) => ("Neo.ClientError.Statement.AccessMode", m.clone()),
+            Self::SessionLimit(m) | Self::RateLimited(m) => {
+                ("Neo.TransientError.Request.ResourceExhaustion", m.clone())
+            }
+            Self::AuthenticationRateLimit(m) => (
+                "Neo.ClientError.Security.AuthenticationRateLimit",
+                m.clone(),
+            ),
+            Self::InvalidBookmark(m) => ("Neo.ClientError.Transaction.InvalidBookmark", m.clone()),
+            Self::BookmarkTimeout(m) => {
+                ("Neo.TransientError.Transaction.BookmarkTimeout", m.clone())
+            }
+            Self::DatabaseNotFound(m) => ("Neo.ClientError.Database.DatabaseNotFound", m.clone()),
+            Self::Conversion(m) => ("Neo.ClientError.Statement.TypeError", m.clone()),
             Self::Backend(m) => ("Neo.DatabaseError.General.UnknownError", m.clone()),
             #[cfg(feature = "ws")]
             Self::WebSocket(m) => ("Neo.TransientError.General.DatabaseUnavailable", m.clone()),
@@ -0,0 +1,255 @@
+//! Authorization hook consulted before queries, transactions and database
+//! switches.
+
+use std::collections::HashSet;
+
+use crate::error::BoltError;
+use crate::server::auth::AuthInfo;
+use crate::server::backend::AccessMode;
+use crate::server::peer::PeerInfo;
+
+/// The request being authorized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    /// RUN, either auto-commit or inside an explicit transaction.
+    Execute,
+    /// BEGIN of an explicit transaction.
+    BeginTransaction,
+    /// Switching the session to the database named in the request.
+    SwitchDatabase,
+}
+
+/// Everything an [`Authorizer`] gets to decide on.
+#[derive(Debug, Clone, Copy)]
+pub struct AuthorizationRequest<'a> {
+    pub operation: Operation,
+    /// Identity from the last LOGON, or `None` without an
+    /// [`AuthValidator`](crate::server::AuthValidator).
+    pub auth: Option<&'a AuthInfo>,
+    /// Access mode of the request or of the enclosing transaction.
+    pub access_mode: AccessMode,
+    /// Target database, or `None` for the default database.
+    pub database: Option<&'a str>,
+    /// Query text, for [`Operation::Execute`].
+    pub query: Option<&'a str>,
+    pub peer: &'a PeerInfo,
+}
+
+/// Decides whether an authenticated client may perform a request.
+///
+/// Invoked by the connection before
+/// [`execute`](crate::server::BoltBackend::execute),
+/// [`begin_transaction`](crate::server::BoltBackend::begin_transaction) and
+/// database switches. Rejections (normally [`BoltError::Forbidden`], which
+/// maps to `Neo.ClientError.Security.Forbidden`) are sent to the client and
+/// the backend is not called.
+#[async_trait::async_trait]
+pub trait Authorizer: Send + Sync + 'static {
+    async fn authorize(&self, request: &AuthorizationRequest<'_>) -> Result<(), BoltError>;
+
+    /// Whether `auth` may only read. The connection then runs all of its
+    /// requests in read mode, as for [`AuthInfo::read_only`], whatever mode
+    /// the client asks for, and writes fail with
+    /// `Neo.ClientError.Statement.AccessMode`. Default: `false`.
+    fn read_only(&self, _auth: &AuthInfo) -> bool {
+        false
+    }
+}
+
+/// Grants read or write access by role.
+///
+/// Principals holding a write role may do anything. Principals holding only
+/// a read role are [read-only](Authorizer::read_only): their requests run in
+/// read mode whatever mode the client asks for, so plain reads work with
+/// driver defaults and writes fail with
+/// `Neo.ClientError.Statement.AccessMode`, from the backend or once the
+/// summary reports a write (see
+/// [`BoltServer::read_only`](crate::server::BoltServer::read_only)). Everyone
+/// else, including unauthenticated clients, is refused with
+/// `Neo.ClientError.Security.Forbidden`.
+///
+/// ```rust
+/// use boltr::server::auth::RoleAuthorizer;
+///
+/// let authorizer = RoleAuthorizer::new()
+///     .read_role("reader")
+///     .write_role("editor")
+///     .write_role("admin");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RoleAuthorizer {
+    read: HashSet<String>,
+    write: HashSet<String>,
+}
+
+impl RoleAuthorizer {
+    /// Creates an authorizer that refuses everything.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Grants read access to `role`.
+    pub fn read_role(mut self, role: impl Into<String>) -> Self {
+        self.read.insert(role.into());
+        self
+    }
+
+    /// Grants read and write access to `role`.
+    pub fn write_role(mut self, role: impl Into<String>) -> Self {
+        self.write.insert(role.into());
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl Authorizer for RoleAuthorizer {
+    async fn authorize(&self, request: &AuthorizationRequest<'_>) -> Result<(), BoltError> {
+        let Some(auth) = request.auth else {
+            return Err(BoltError::Forbidden("not authenticated".into()));
+        };
+        if holds(&self.write, auth) || holds(&self.read, auth) {
+            Ok(())
+        } else {
+            Err(BoltError::Forbidden(format!(
+                "{} may not {}",
+                auth.principal,
+                match request.operation {
+                    Operation::Execute => "run queries",
+                    Operation::BeginTransaction => "begin transactions",
+                    Operation::SwitchDatabase => "switch databases",
+                },
+            )))
+        }
+    }
+
+    fn read_only(&self, auth: &AuthInfo) -> bool {
+        !holds(&self.write, auth) && holds(&self.read, auth)
+    }
+}
+
+/// Whether `auth` holds any of `roles`.
+fn holds(roles: &HashSet<String>, auth: &AuthInfo) -> bool {
+    auth.roles.iter().any(|r| roles.contains(r))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn roles_grant_read_and_write() {
+        let authorizer = RoleAuthorizer::new()
+            .read_role("reader")
+            .write_role("admin");
+        let peer = PeerInfo::new("127.0.0.1:9999".parse().unwrap());
+        let user = |roles: &[&str]| AuthInfo {
+            principal: "alice".into(),
+            roles: roles.iter().map(|r| r.to_string()).collect(),
+            ..AuthInfo::default()
+        };
+        let allowed = async |auth: Option<&AuthInfo>, mode| {
+            let request = AuthorizationRequest {
+                operation: Operation::Execute,
+                auth,
+                access_mode: mode,
+                database: None,
+                query: Some("RETURN 1"),
+                peer: &peer,
+            };
+            authorizer.authorize(&request).await.is_ok()
+        };
+
+        let (reader, admin, nobody) = (user(&["reader"]), user(&["admin"]), user(&[]));
+        let both = user(&["reader", "admin"]);
+        assert!(allowed(Some(&reader), AccessMode::Read).await);
+        assert!(allowed(Some(&admin), AccessMode::Write).await);
+        assert!(!allowed(Some(&nobody), AccessMode::Read).await);
+        assert!(!allowed(None, AccessMode::Read).await);
+        // Readers are held to read mode by the connection instead.
+        assert!(authorizer.read_only(&reader));
+        assert!(!authorizer.read_only(&admin));
+        assert!(!authorizer.read_only(&both));
+        assert!(!authorizer.read_only(&nobody));
+    }
+
+    #[tokio::test]
+    async fn readers_are_held_to_read_mode() {
+        use crate::message::{ClientMessage, ServerMessage};
+        use crate::server::BoltServer;
+        use crate::server::auth::StaticUsers;
+        use crate::server::test_support::{
+            MockBackend, TestClient, failure_code, hello, pull, run,
+        };
+        use crate::types::{BoltDict, BoltValue};
+
+        let mut backend = MockBackend::default();
+        backend.ignores_access_mode = true;
+        let handle = BoltServer::builder(backend.clone())
+            .auth(
+                StaticUsers::new()
+                    .user_with_roles("alice", "secret", ["reader"])
+                    .user("bob", "secret"),
+            )
+            .authorizer(RoleAuthorizer::new().read_role("reader"))
+            .transactional_reads(true)
+            .bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+
+        let string = |s: &str| BoltValue::String(s.into());
+        let log_on = async |principal: &str| {
+            let mut client = TestClient::connect(handle.local_addr()).await.unwrap();
+            client.request(hello()).await.unwrap();
+            let reply = client
+                .request(ClientMessage::Logon {
+                    auth: BoltDict::from([
+                        ("scheme".into(), string("basic")),
+                        ("principal".into(), string(principal)),
+                        ("credentials".into(), string("secret")),
+                    ]),
+                })
+                .await
+                .unwrap();
+            assert!(matches!(reply, ServerMessage::Success { .. }));
+            client
+        };
+        let mut client = log_on("alice").await;
+
+        // Reads work with the driver default of write mode.
+        let reply = client.request(run("RETURN 1")).await.unwrap();
+        assert!(matches!(reply, ServerMessage::Success { .. }));
+        client.request(pull()).await.unwrap();
+        client.recv().await.unwrap();
+        let begin = ClientMessage::Begin {
+            extra: BoltDict::new(),
+        };
+        let reply = client.request(begin).await.unwrap();
+        assert!(matches!(reply, ServerMessage::Success { .. }));
+        client.request(ClientMessage::Rollback).await.unwrap();
+
+        // Writes fail in any mode.
+        let access_mode = Some("Neo.ClientError.Statement.AccessMode");
+        let create = |mode: &str| ClientMessage::Run {
+            query: "CREATE ()".into(),
+            parameters: BoltDict::new(),
+            extra: BoltDict::from([("mode".into(), string(mode))]),
+        };
+        for mode in ["w", "r"] {
+            let reply = client.request(create(mode)).await.unwrap();
+            assert_eq!(failure_code(&reply), access_mode);
+            client.request(ClientMessage::Reset).await.unwrap();
+        }
+        assert_eq!(backend.rolled_back.lock().unwrap().len(), 3);
+
+        // Principals without a role are refused outright.
+        let mut client = log_on("bob").await;
+        let reply = client.request(run("RETURN 1")).await.unwrap();
+        assert_eq!(
+            failure_code(&reply),
+            Some("Neo.ClientError.Security.Forbidden")
+        );
+
+        handle.shutdown();
+        handle.wait().await.unwrap();
+    }
+}
@@ -0,0 +1,82 @@
+//! Authentication validator trait for Bolt servers, and ready-made
+//! validators:
+//!
+//! - [`StaticUsers`]: an in-memory user table (scheme `basic`).
+//! - [`PasswordFile`]: argon2-hashed users loaded from a file, reloadable
+//!   at runtime (feature `password`).
+//! - [`JwtValidator`]: locally verified bearer tokens (feature `jwt`).
+//!
+//! Permissions are checked separately by an [`Authorizer`], such as
+//! [`RoleAuthorizer`], based on the [`AuthInfo`] the validator returned.
+
+mod authorizer;
+#[cfg(feature = "jwt")]
+mod jwt;
+#[cfg(feature = "password")]
+mod password_file;
+mod static_users;
+
+use std::time::SystemTime;
+
+use crate::error::BoltError;
+use crate::server::AuthCredentials;
+use crate::types::BoltDict;
+
+pub use authorizer::{AuthorizationRequest, Authorizer, Operation, RoleAuthorizer};
+#[cfg(feature = "jwt")]
+pub use jwt::JwtValidator;
+#[cfg(feature = "password")]
+pub use password_file::PasswordFile;
+pub use static_users::StaticUsers;
+
+/// Authenticated identity returned by the validator.
+#[derive(Debug, Clone, Default)]
+pub struct AuthInfo {
+    /// Authenticated principal (user/token identifier).
+    pub principal: String,
+    /// If true, the client should change credentials before proceeding.
+    pub credentials_expired: bool,
+    /// Roles granted to the principal.
+    pub roles: Vec<String>,
+    /// Restricts the principal to read access, whatever mode its requests ask for.
+    pub read_only: bool,
+    /// When the credentials stop being valid. Once passed, requests fail
+    /// with `Neo.ClientError.Security.TokenExpired` until the client logs
+    /// on again.
+    pub expires_at: Option<SystemTime>,
+    /// Further claims about the principal (e.g. the verified JWT claims),
+    /// for use by an [`Authorizer`] or the backend.
+    pub claims: BoltDict,
+}
+
+/// Validates authentication credentials during the LOGON phase.
+#[async_trait::async_trait]
+pub trait AuthValidator: Send + Sync + 'static {
+    /// Validate the given credentials.
+    /// Return `Ok(AuthInfo)` with the authenticated identity, or `Err(BoltError)` to reject.
+    async fn validate(&self, credentials: &AuthCredentials) -> Result<AuthInfo, BoltError>;
+}
+
+/// Rejection shared by the built-in validators; it does not reveal whether
+/// the user exists.
+fn invalid_credentials() -> BoltError {
+    BoltError::Authentication("invalid username or password".into())
+}
+
+/// Checks that `credentials` use `scheme` and returns the principal and secret.
+fn require_scheme<'a>(
+    credentials: &'a AuthCredentials,
+    scheme: &str,
+) -> Result<(Option<&'a str>, &'a str), BoltError> {
+    if credentials.scheme != scheme {
+        return Err(BoltError::Authentication(format!(
+            "unsupported authentication scheme: {}",
+            credentials.scheme
+        )));
+    }
+    let secret = credentials
+        .credentials
+        .as_deref()
+        .ok_or_else(invalid_credentials)?;
+    Ok((credentials.principal.as_deref(), secret))
+}
@@ -0,0 +1,234 @@
+//! Users with argon2 password hashes, loaded from a file (feature `password`).
+
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock, RwLock};
+
+use argon2::Argon2;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+
+use crate::error::BoltError;
+use crate::server::AuthCredentials;
+use crate::server::auth::{AuthInfo, AuthValidator, invalid_credentials, require_scheme};
+
+struct User {
+    hash: String,
+    roles: Vec<String>,
+}
+
+/// Validates `basic` credentials against a file of argon2 password hashes.
+///
+/// Each non-empty line not starting with `#` holds a user name, a PHC hash
+/// string and an optional comma-separated role list, separated by colons:
+///
+/// ```text
+/// # user:hash[:roles]
+/// alice:$argon2id$v=19$m=19456,t=2,p=1$c2FsdHNhbHQ$...:admin,reader
+/// bob:$argon2id$v=19$m=19456,t=2,p=1$b3RoZXJzYWx0$...
+/// ```
+///
+/// Hashes can be produced with [`PasswordFile::hash_password`]. Call
+/// [`reload`](Self::reload) (e.g. on `SIGHUP`) to pick up changes; LOGONs
+/// already in progress finish against the previous table.
+///
+/// ```rust,no_run
+/// # fn example() -> Result<(), boltr::error::BoltError> {
+/// use std::sync::Arc;
+/// use boltr::server::auth::PasswordFile;
+///
+/// let users = Arc::new(PasswordFile::load("/etc/boltr/users")?);
+/// // ... hand a clone to `BoltServer::auth()`, later:
+/// users.reload()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct PasswordFile {
+    path: PathBuf,
+    users: RwLock<Arc<HashMap<String, User>>>,
+}
+
+impl PasswordFile {
+    /// Loads the user table from `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, BoltError> {
+        let path = path.as_ref().to_path_buf();
+        let users = read_users(&path)?;
+        Ok(Self {
+            path,
+            users: RwLock::new(Arc::new(users)),
+        })
+    }
+
+    /// Re-reads the file. On error the current table stays in effect.
+    pub fn reload(&self) -> Result<(), BoltError> {
+        let users = read_users(&self.path)?;
+        *self.users.write().unwrap() = Arc::new(users);
+        Ok(())
+    }
+
+    /// Returns the number of users in the current table.
+    pub fn len(&self) -> usize {
+        self.users.read().unwrap().len()
+    }
+
+    /// Returns `true` if the current table has no users.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Hashes `password` with argon2id and a random salt, in the PHC string
+    /// format expected in the file.
+    pub fn hash_password(password: &str) -> Result<String, BoltError> {
+        let salt = SaltString::encode_b64(uuid::Uuid::new_v4().as_bytes())
+            .map_err(|e| BoltError::Backend(e.to_string()))?;
+        Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+            .map_err(|e| BoltError::Backend(e.to_string()))
+    }
+}
+
+#[async_trait::async_trait]
+impl AuthValidator for PasswordFile {
+    async fn validate(&self, credentials: &AuthCredentials) -> Result<AuthInfo, BoltError> {
+        let (principal, password) = require_scheme(credentials, "basic")?;
+        let principal = principal.ok_or_else(invalid_credentials)?;
+        let users = self.users.read().unwrap().clone();
+        let user = users.get(principal);
+
+        // Unknown users are checked against a dummy hash so that response
+        // times do not reveal which names exist.
+        let hash = match user {
+            Some(user) => user.hash.clone(),
+            None => dummy_hash().to_string(),
+        };
+        let password = password.to_string();
+        let verified = tokio::task::spawn_blocking(move || {
+            PasswordHash::new(&hash).is_ok_and(|hash| {
+                Argon2::default()
+                    .verify_password(password.as_bytes(), &hash)
+                    .is_ok()
+            })
+        })
+        .await
+        .map_err(|e| BoltError::Backend(e.to_string()))?;
+
+        match user {
+            Some(user) if verified => Ok(AuthInfo {
+                principal: principal.to_string(),
+                roles: user.roles.clone(),
+                ..AuthInfo::default()
+            }),
+            _ => Err(invalid_credentials()),
+        }
+    }
+}
+
+fn dummy_hash() -> &'static str {
+    static HASH: OnceLock<String> = OnceLock::new();
+    HASH.get_or_init(|| PasswordFile::hash_password("").unwrap_or_default())
+}
+
+fn read_users(path: &Path) -> Result<HashMap<String, User>, BoltError> {
+    let contents = std::fs::read_to_string(path)?;
+    parse_users(&contents).map_err(|(line, message)| {
+        BoltError::Io(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}:{line}: {message}", path.display()),
+        ))
+    })
+}
+
+/// Parses the file format; errors carry the 1-based line number.
+fn parse_users(contents: &str) -> Result<HashMap<String, User>, (usize, String)> {
+    let mut users = HashMap::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut fields = line.splitn(3, ':');
+        let name = fields.next().unwrap_or_default();
+        let hash = fields.next().unwrap_or_default();
+        if name.is_empty() || hash.is_empty() {
+            return Err((index + 1, "expected user:hash[:roles]".into()));
+        }
+        if let Err(e) = PasswordHash::new(hash) {
+            return Err((index + 1, format!("invalid password hash: {e}")));
+        }
+        let roles = fields
+            .next()
+            .map(|roles| {
+                roles
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|r| !r.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+        users.insert(
+            name.to_string(),
+            User {
+                hash: hash.to_string(),
+                roles,
+            },
+        );
+    }
+    Ok(users)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::test_support::credentials;
+
+    fn temp_file(contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("boltr-users-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn validates_and_reloads() {
+        let alice = PasswordFile::hash_password("secret").unwrap();
+        let path = temp_file(&format!("# users\nalice:{alice}:admin, reader\n"));
+        let users = PasswordFile::load(&path).unwrap();
+
+        let info = users
+            .validate(&credentials("basic", Some("alice"), Some("secret")))
+            .await
+            .unwrap();
+        assert_eq!(info.principal, "alice");
+        assert_eq!(info.roles, ["admin", "reader"]);
+        assert!(
+            users
+                .validate(&credentials("basic", Some("alice"), Some("wrong")))
+                .await
+                .is_err()
+        );
+        assert!(
+            users
+                .validate(&credentials("basic", Some("bob"), Some("secret")))
+                .await
+                .is_err()
+        );
+
+        let bob = PasswordFile::hash_password("hunter2").unwrap();
+        std::fs::write(&path, format!("bob:{bob}\n")).unwrap();
+        users.reload().unwrap();
+        assert_eq!(users.len(), 1);
+        let info = users
+            .validate(&credentials("basic", Some("bob"), Some("hunter2")))
+            .await
+            .unwrap();
+        assert!(info.roles.is_empty());
+
+        // A broken file keeps the previous table.
+        std::fs::write(&path, "carol:not-a-hash\n").unwrap();
+        assert!(users.reload().is_err());
+        assert_eq!(users.len(), 1);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
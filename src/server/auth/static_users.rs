@@ -0,0 +1,119 @@
+//! In-memory user table.
+
+use std::collections::HashMap;
+
+use crate::error::BoltError;
+use crate::server::AuthCredentials;
+use crate::server::auth::{AuthInfo, AuthValidator, invalid_credentials, require_scheme};
+
+struct User {
+    password: String,
+    roles: Vec<String>,
+}
+
+/// Validates `basic` credentials against a fixed table of users with
+/// plaintext passwords. Intended for tests, demos and embedded servers;
+/// use [`PasswordFile`](crate::server::auth::PasswordFile) when passwords
+/// must not be held in the clear.
+///
+/// ```rust
+/// use boltr::server::BoltServer;
+/// use boltr::server::auth::StaticUsers;
+/// # use boltr::server::BoltBackend;
+///
+/// # fn example(backend: impl BoltBackend) {
+/// let users = StaticUsers::new()
+///     .user("alice", "s3cret")
+///     .user_with_roles("admin", "hunter2", ["admin"]);
+/// let server = BoltServer::builder(backend).auth(users);
+/// # }
+/// ```
+#[derive(Default)]
+pub struct StaticUsers {
+    users: HashMap<String, User>,
+}
+
+impl StaticUsers {
+    /// Creates an empty table, which rejects every LOGON.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a user without roles.
+    pub fn user(self, name: impl Into<String>, password: impl Into<String>) -> Self {
+        self.user_with_roles(name, password, std::iter::empty::<String>())
+    }
+
+    /// Adds a user with the given roles, reported in [`AuthInfo::roles`].
+    pub fn user_with_roles(
+        mut self,
+        name: impl Into<String>,
+        password: impl Into<String>,
+        roles: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.users.insert(
+            name.into(),
+            User {
+                password: password.into(),
+                roles: roles.into_iter().map(Into::into).collect(),
+            },
+        );
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl AuthValidator for StaticUsers {
+    async fn validate(&self, credentials: &AuthCredentials) -> Result<AuthInfo, BoltError> {
+        let (principal, password) = require_scheme(credentials, "basic")?;
+        let principal = principal.ok_or_else(invalid_credentials)?;
+        match self.users.get(principal) {
+            Some(user) if constant_time_eq(user.password.as_bytes(), password.as_bytes()) => {
+                Ok(AuthInfo {
+                    principal: principal.to_string(),
+                    roles: user.roles.clone(),
+                    ..AuthInfo::default()
+                })
+            }
+            _ => Err(invalid_credentials()),
+        }
+    }
+}
+
+/// Compares without short-circuiting on the first differing byte.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::test_support::credentials;
+
+    #[tokio::test]
+    async fn validates_basic_credentials() {
+        let users = StaticUsers::new().user("alice", "secret").user_with_roles(
+            "bob",
+            "hunter2",
+            ["admin", "reader"],
+        );
+
+        let info = users
+            .validate(&credentials("basic", Some("bob"), Some("hunter2")))
+            .await
+            .unwrap();
+        assert_eq!(info.principal, "bob");
+        assert_eq!(info.roles, ["admin", "reader"]);
+
+        for (scheme, user, password) in [
+            ("basic", Some("alice"), Some("wrong")),
+            ("basic", Some("carol"), Some("secret")),
+            ("basic", None, Some("secret")),
+            ("basic", Some("alice"), None),
+            ("bearer", Some("alice"), Some("secret")),
+        ] {
+            let result = users.validate(&credentials(scheme, user, password)).await;
+            assert!(matches!(result, Err(BoltError::Authentication(_))));
+        }
+    }
+}
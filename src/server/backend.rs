@@ -4,7 +4,8 @@ use std::collections::HashMap;
 
 use crate::error::BoltError;
 use crate::server::auth::AuthInfo;
-use crate::types::{BoltDict, BoltValue};
+use crate::server::peer::PeerInfo;
+use crate::types::{BoltDict, BoltValue, ResultSummary};
 
 /// Opaque handle identifying a Bolt session (one per TCP connection).
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -23,6 +24,15 @@ pub struct SessionConfig {
 /// A session property that can be modified.
 pub enum SessionProperty {
     Database(String),
+    /// Access mode for the following auto-commit queries and transactions.
+    /// Sessions start in [`AccessMode::Write`] and return to it on
+    /// [`reset_session`](BoltBackend::reset_session); this is only sent when
+    /// the mode changes. Backends should reject writes in read mode with
+    /// [`BoltError::AccessMode`]. The server also fails reads whose summary
+    /// reports a write, and can run auto-commit reads in a transaction to
+    /// roll them back; see
+    /// [`BoltServer::transactional_reads`](crate::server::BoltServer::transactional_reads).
+    AccessMode(AccessMode),
 }
 
 /// Transaction access mode.
@@ -32,12 +42,25 @@ pub enum AccessMode {
     Write,
 }
 
+impl AccessMode {
+    /// Reads the `mode` entry of a RUN or BEGIN `extra` dictionary:
+    /// `"r"` is read, anything else (including absence) is write.
+    pub fn from_extra(extra: &BoltDict) -> Self {
+        match extra.get("mode").and_then(|v| v.as_str()) {
+            Some("r") => Self::Read,
+            _ => Self::Write,
+        }
+    }
+}
+
 /// Authentication credentials extracted from HELLO/LOGON.
 #[derive(Debug, Clone)]
 pub struct AuthCredentials {
     pub scheme: String,
     pub principal: Option<String>,
     pub credentials: Option<String>,
+    /// The remote peer, including its verified client certificate under mutual TLS.
+    pub peer: PeerInfo,
 }
 
 /// A single row of query results.
@@ -58,7 +81,9 @@ pub struct ResultMetadata {
 pub struct ResultStream {
     pub metadata: ResultMetadata,
     pub records: Vec<BoltRecord>,
-    pub summary: BoltDict,
+    /// Sent with the final PULL or DISCARD. The connection fills in `db`
+    /// and measures `t_first` and `t_last` when they are left empty.
+    pub summary: ResultSummary,
 }
 
 /// A server address with a role in the routing table.
@@ -128,6 +153,9 @@ pub fn extract_bookmarks(extra: &BoltDict) -> Vec<String> {
 /// After a successful COMMIT, the server should include `"bookmark"` in the
 /// returned metadata dict. The driver will use this bookmark in subsequent
 /// transactions to ensure causal ordering.
+/// Alternatively, configure a
+/// [`BookmarkManager`](crate::server::BookmarkManager), which issues and
+/// enforces bookmarks for the backend.
 #[async_trait::async_trait]
 pub trait BoltBackend: Send + Sync + 'static {
     // -- Session lifecycle --
@@ -147,6 +175,19 @@ pub trait BoltBackend: Send + Sync + 'static {
         Ok(())
     }
 
+    /// Resolves the home database of a principal, used for requests that
+    /// name no database. Called once per LOGON; `principal` is `None` for
+    /// anonymous clients.
+    ///
+    /// When this returns a name, the connection switches the session to it
+    /// before such requests, reports it as `db` in BEGIN, RUN and PULL
+    /// replies, and passes it to [`route`](Self::route). Drivers cache it per
+    /// user, so it should be stable for a principal. The default returns
+    /// `None`: the backend picks the database and `db` is not reported.
+    async fn home_database(&self, _principal: Option<&str>) -> Result<Option<String>, BoltError> {
+        Ok(None)
+    }
+
     /// Close a session and release resources. Called on GOODBYE or disconnect.
     async fn close_session(&self, session: &SessionHandle) -> Result<(), BoltError>;
 
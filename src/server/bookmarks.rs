@@ -0,0 +1,237 @@
+//! Server-side bookmarks for causal consistency.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tokio::sync::watch;
+
+use crate::error::BoltError;
+
+/// Issues and enforces bookmarks, so a client that passes the bookmark of
+/// one transaction to the next always sees its own writes.
+///
+/// Each database has a commit position. Every COMMIT, and every auto-commit
+/// query that wrote, advances it and is answered with the bookmark
+/// `bk:<database>:<position>`; read-only auto-commit queries get the
+/// current one. BEGIN and auto-commit RUN wait until the positions of the
+/// bookmarks they carry are visible, failing with
+/// `Neo.TransientError.Transaction.BookmarkTimeout` after
+/// [`wait_timeout`](Self::wait_timeout). Malformed bookmarks, and bookmarks
+/// past position 0 of a database that has neither committed nor been
+/// [advanced](Self::advance), fail with
+/// `Neo.ClientError.Transaction.InvalidBookmark`.
+///
+/// A commit is visible as soon as the backend returns from it. Backends that
+/// apply commits elsewhere first, such as read replicas, or that restart
+/// from persisted state report their progress with
+/// [`advance`](Self::advance).
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use boltr::server::BookmarkManager;
+///
+/// let bookmarks = Arc::new(BookmarkManager::new().wait_timeout(Duration::from_secs(5)));
+/// // Restore the position persisted by the backend.
+/// bookmarks.advance("neo4j", 1042);
+/// assert_eq!(bookmarks.position("neo4j"), 1042);
+/// ```
+#[derive(Debug)]
+pub struct BookmarkManager {
+    positions: Mutex<HashMap<String, watch::Sender<u64>>>,
+    default_database: String,
+    wait_timeout: Duration,
+}
+
+impl Default for BookmarkManager {
+    fn default() -> Self {
+        Self {
+            positions: Mutex::default(),
+            default_database: "neo4j".into(),
+            wait_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+impl BookmarkManager {
+    /// Creates a manager that knows no databases yet: bookmarks past
+    /// position 0 are rejected as invalid until the database has committed
+    /// or been [advanced](Self::advance). Waits for bookmarks for up to 30
+    /// seconds and issues them for `neo4j` when no database is named.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// How long BEGIN and RUN wait for their bookmarks. Default: 30 seconds.
+    pub fn wait_timeout(mut self, timeout: Duration) -> Self {
+        self.wait_timeout = timeout;
+        self
+    }
+
+    /// Database that bookmarks are issued for when a request names none and
+    /// no home database is known. Default: `neo4j`.
+    pub fn default_database(mut self, name: &str) -> Self {
+        self.default_database = name.to_ascii_lowercase();
+        self
+    }
+
+    /// The visible commit position of `database`.
+    pub fn position(&self, database: &str) -> u64 {
+        self.lookup(database).map_or(0, |sender| *sender.borrow())
+    }
+
+    /// Reports that every commit up to `position` is visible in `database`,
+    /// waking requests waiting for it. Positions never move backwards.
+    pub fn advance(&self, database: &str, position: u64) {
+        self.sender(database).send_if_modified(|current| {
+            let advanced = position > *current;
+            if advanced {
+                *current = position;
+            }
+            advanced
+        });
+    }
+
+    /// Records a commit in `database` and returns its bookmark.
+    pub(crate) fn commit(&self, database: Option<&str>) -> String {
+        let database = self.database(database);
+        let mut position = 0;
+        self.sender(&database).send_modify(|current| {
+            *current += 1;
+            position = *current;
+        });
+        bookmark(&database, position)
+    }
+
+    /// The bookmark of the latest visible commit in `database`.
+    pub(crate) fn current(&self, database: Option<&str>) -> String {
+        let database = self.database(database);
+        let position = self.position(&database);
+        bookmark(&database, position)
+    }
+
+    /// Waits until every bookmark is visible. Fails at once on a malformed
+    /// bookmark or one of an unknown database, and after the wait timeout
+    /// otherwise.
+    pub(crate) async fn wait(&self, bookmarks: &[String]) -> Result<(), BoltError> {
+        let mut targets = Vec::with_capacity(bookmarks.len());
+        for b in bookmarks {
+            let (database, position) = parse(b)?;
+            // Look up without inserting: only commits and `advance` create
+            // databases, so clients cannot grow the map with made-up names.
+            match self.lookup(database) {
+                Some(sender) => targets.push((sender, position)),
+                None if position == 0 => {}
+                None => {
+                    return Err(BoltError::InvalidBookmark(format!(
+                        "bookmark {b:?} names an unknown database"
+                    )));
+                }
+            }
+        }
+        let all_visible = async {
+            for (sender, position) in targets {
+                let mut receiver = sender.subscribe();
+                // The sender is held, so the channel cannot close.
+                let _ = receiver.wait_for(|&visible| visible >= position).await;
+            }
+        };
+        tokio::time::timeout(self.wait_timeout, all_visible)
+            .await
+            .map_err(|_| {
+                BoltError::BookmarkTimeout(format!(
+                    "bookmarks not reached within {:?}",
+                    self.wait_timeout
+                ))
+            })
+    }
+
+    fn database(&self, database: Option<&str>) -> String {
+        database.map_or_else(|| self.default_database.clone(), str::to_ascii_lowercase)
+    }
+
+    fn lookup(&self, database: &str) -> Option<watch::Sender<u64>> {
+        self.positions
+            .lock()
+            .unwrap()
+            .get(&database.to_ascii_lowercase())
+            .cloned()
+    }
+
+    fn sender(&self, database: &str) -> watch::Sender<u64> {
+        self.positions
+            .lock()
+            .unwrap()
+            .entry(database.to_ascii_lowercase())
+            .or_insert_with(|| watch::Sender::new(0))
+            .clone()
+    }
+}
+
+fn bookmark(database: &str, position: u64) -> String {
+    format!("bk:{database}:{position}")
+}
+
+/// Splits `bk:<database>:<position>`.
+fn parse(bookmark: &str) -> Result<(&str, u64), BoltError> {
+    bookmark
+        .strip_prefix("bk:")
+        .and_then(|rest| rest.rsplit_once(':'))
+        .filter(|(database, _)| !database.is_empty())
+        .and_then(|(database, position)| Some((database, position.parse().ok()?)))
+        .ok_or_else(|| BoltError::InvalidBookmark(format!("invalid bookmark: {bookmark:?}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn waits_until_the_position_is_visible() {
+        let manager =
+            std::sync::Arc::new(BookmarkManager::new().wait_timeout(Duration::from_millis(500)));
+        assert_eq!(manager.commit(None), "bk:neo4j:1");
+        assert_eq!(manager.commit(Some("Sales")), "bk:sales:1");
+        assert_eq!(manager.current(Some("sales")), "bk:sales:1");
+        manager.wait(&["bk:neo4j:1".into()]).await.unwrap();
+
+        // A bookmark from another instance blocks until it is reported.
+        let waiter = {
+            let manager = manager.clone();
+            tokio::spawn(async move { manager.wait(&["bk:sales:3".into()]).await })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!waiter.is_finished());
+        manager.advance("sales", 3);
+        waiter.await.unwrap().unwrap();
+
+        assert!(matches!(
+            manager.wait(&["bk:sales:4".into()]).await,
+            Err(BoltError::BookmarkTimeout(_))
+        ));
+        for malformed in ["bk:sales", "sales:1", "bk::1", "bk:sales:x"] {
+            assert!(matches!(
+                manager.wait(&[malformed.into()]).await,
+                Err(BoltError::InvalidBookmark(_))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn unknown_databases_are_not_recorded() {
+        let manager = BookmarkManager::new();
+        manager.commit(Some("sales"));
+        manager.wait(&["bk:other:0".into()]).await.unwrap();
+        assert!(matches!(
+            manager.wait(&["bk:other:1".into()]).await,
+            Err(BoltError::InvalidBookmark(_))
+        ));
+        assert_eq!(manager.position("other"), 0);
+        assert_eq!(manager.current(Some("other")), "bk:other:0");
+        assert_eq!(manager.positions.lock().unwrap().len(), 1);
+
+        manager.advance("other", 1);
+        manager.wait(&["bk:other:1".into()]).await.unwrap();
+    }
+}
@@ -7,48 +7,25 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use tokio::net::TcpListener;
+use tokio::sync::{Semaphore, watch};
+use tokio::task::JoinSet;
 
 use crate::error::BoltError;
-use crate::server::auth::AuthValidator;
+use crate::server::auth::{AuthValidator, Authorizer};
 use crate::server::backend::BoltBackend;
-use crate::server::connection::Connection;
-use crate::server::handshake::server_handshake;
+use crate::server::bookmarks::BookmarkManager;
+use crate::server::context::{ServerContext, Timeouts, Transport};
+use crate::server::handle::ServerHandle;
+use crate::server::limits::{AuthLockout, LockoutTracker, RateLimit, RateLimiter};
+use crate::server::listener::BoltListener;
+use crate::server::proxy::ProxyProtocol;
+use crate::server::service::BoltService;
 use crate::server::session_manager::SessionManager;
 
+#[cfg(feature = "ws")]
+use crate::server::sniff::HttpHandler;
 #[cfg(feature = "tls")]
-use rustls_pki_types::pem::PemObject;
-#[cfg(feature = "tls")]
-use rustls_pki_types::{CertificateDer, PrivateKeyDer};
-#[cfg(feature = "tls")]
-use tokio_rustls::TlsAcceptor;
-
-/// TLS configuration for the Bolt server.
-#[cfg(feature = "tls")]
-pub struct TlsConfig {
-    acceptor: TlsAcceptor,
-}
-
-#[cfg(feature = "tls")]
-impl TlsConfig {
-    /// Creates a TLS configuration from PEM-encoded certificate and key bytes.
-    pub fn from_pem(cert_pem: &[u8], key_pem: &[u8]) -> Result<Self, BoltError> {
-        let certs: Vec<CertificateDer<'static>> = CertificateDer::pem_slice_iter(cert_pem)
-            .collect::<Result<_, _>>()
-            .map_err(|e| BoltError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, e)))?;
-
-        let key = PrivateKeyDer::from_pem_slice(key_pem)
-            .map_err(|e| BoltError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, e)))?;
-
-        let config = tokio_rustls::rustls::ServerConfig::builder()
-            .with_no_client_auth()
-            .with_single_cert(certs, key)
-            .map_err(|e| BoltError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, e)))?;
-
-        Ok(Self {
-            acceptor: TlsAcceptor::from(Arc::new(config)),
-        })
-    }
-}
+use crate::server::tls::TlsConfig;
 
 /// Builder for configuring and starting a Bolt server.
 ///
@@ -73,12 +50,27 @@ impl TlsConfig {
 pub struct BoltServer<B: BoltBackend> {
     backend: B,
     auth_validator: Option<Arc<dyn AuthValidator>>,
+    authorizer: Option<Arc<dyn Authorizer>>,
+    bookmark_manager: Option<Arc<BookmarkManager>>,
     idle_timeout: Option<Duration>,
     max_sessions: Option<usize>,
+    max_sessions_per_ip: Option<usize>,
+    max_sessions_per_principal: Option<usize>,
+    rate_limit: Option<RateLimit>,
+    auth_lockout: Option<AuthLockout>,
     max_message_size: Option<usize>,
+    read_only: bool,
+    transactional_reads: bool,
+    recv_timeout: Option<Duration>,
+    proxy_protocol: Option<ProxyProtocol>,
+    timeouts: Timeouts,
+    max_unauthenticated_connections: Option<usize>,
+    shutdown_grace_period: Duration,
     shutdown: Option<Pin<Box<dyn Future<Output = ()> + Send>>>,
     #[cfg(feature = "tls")]
     tls_config: Option<TlsConfig>,
+    #[cfg(feature = "ws")]
+    http_handler: Option<Arc<dyn HttpHandler>>,
 }
 
 impl<B: BoltBackend> BoltServer<B> {
@@ -87,12 +79,27 @@ impl<B: BoltBackend> BoltServer<B> {
         Self {
             backend,
             auth_validator: None,
+            authorizer: None,
+            bookmark_manager: None,
             idle_timeout: None,
             max_sessions: None,
+            max_sessions_per_ip: None,
+            max_sessions_per_principal: None,
+            rate_limit: None,
+            auth_lockout: None,
             max_message_size: None,
+            read_only: false,
+            transactional_reads: false,
+            recv_timeout: None,
+            proxy_protocol: None,
+            timeouts: Timeouts::default(),
+            max_unauthenticated_connections: None,
+            shutdown_grace_period: Duration::ZERO,
             shutdown: None,
             #[cfg(feature = "tls")]
             tls_config: None,
+            #[cfg(feature = "ws")]
+            http_handler: None,
         }
     }
 
@@ -102,6 +109,22 @@ impl<B: BoltBackend> BoltServer<B> {
         self
     }
 
+    /// Sets an authorizer, consulted before every query, transaction and
+    /// database switch. See [`Authorizer`].
+    pub fn authorizer(mut self, authorizer: impl Authorizer) -> Self {
+        self.authorizer = Some(Arc::new(authorizer));
+        self
+    }
+
+    /// Issues bookmarks on COMMIT and auto-commit results and makes BEGIN
+    /// and RUN wait for the bookmarks they carry. See [`BookmarkManager`];
+    /// keep a clone to report commit positions with
+    /// [`BookmarkManager::advance`].
+    pub fn bookmark_manager(mut self, manager: Arc<BookmarkManager>) -> Self {
+        self.bookmark_manager = Some(manager);
+        self
+    }
+
     /// Enables TLS with the given configuration.
     #[cfg(feature = "tls")]
     pub fn tls(mut self, config: TlsConfig) -> Self {
@@ -109,6 +132,15 @@ impl<B: BoltBackend> BoltServer<B> {
         self
     }
 
+    /// Sets the handler for plain HTTP requests (e.g. health checks) on a
+    /// port served with [`serve_auto`](Self::serve_auto). Without one, such
+    /// requests get `404 Not Found`.
+    #[cfg(feature = "ws")]
+    pub fn http_handler(mut self, handler: impl HttpHandler) -> Self {
+        self.http_handler = Some(Arc::new(handler));
+        self
+    }
+
     /// Sets the idle session timeout.
     pub fn idle_timeout(mut self, timeout: Duration) -> Self {
         self.idle_timeout = Some(timeout);
@@ -121,6 +153,64 @@ impl<B: BoltBackend> BoltServer<B> {
         self
     }
 
+    /// Caps the concurrent sessions from a single remote IP (the client
+    /// address from the PROXY header, if enabled). HELLO beyond the cap fails
+    /// with the transient `Neo.TransientError.Request.ResourceExhaustion`.
+    pub fn max_sessions_per_ip(mut self, limit: usize) -> Self {
+        self.max_sessions_per_ip = Some(limit);
+        self
+    }
+
+    /// Caps the concurrent sessions authenticated as a single principal.
+    /// LOGON beyond the cap fails like [`max_sessions_per_ip`](Self::max_sessions_per_ip).
+    pub fn max_sessions_per_principal(mut self, limit: usize) -> Self {
+        self.max_sessions_per_principal = Some(limit);
+        self
+    }
+
+    /// Limits the rate of RUN and BEGIN requests per principal (per remote
+    /// IP before authentication). Requests over the limit fail with the
+    /// transient `Neo.TransientError.Request.ResourceExhaustion`, which
+    /// drivers retry.
+    pub fn rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limit = Some(limit);
+        self
+    }
+
+    /// Locks out principals and remote IPs after repeated LOGON failures.
+    /// See [`AuthLockout`].
+    pub fn auth_lockout(mut self, policy: AuthLockout) -> Self {
+        self.auth_lockout = Some(policy);
+        self
+    }
+
+    /// Sets how long a client has from connecting until the Bolt handshake
+    /// completes, including any PROXY header, TLS handshake and WebSocket
+    /// upgrade. Slower connections are closed.
+    pub fn handshake_timeout(mut self, timeout: Duration) -> Self {
+        self.timeouts.handshake = Some(timeout);
+        self
+    }
+
+    /// Sets how long a client has after the handshake to send HELLO.
+    pub fn hello_timeout(mut self, timeout: Duration) -> Self {
+        self.timeouts.hello = Some(timeout);
+        self
+    }
+
+    /// Sets how long a client has after HELLO (or LOGOFF) to send LOGON.
+    pub fn logon_timeout(mut self, timeout: Duration) -> Self {
+        self.timeouts.logon = Some(timeout);
+        self
+    }
+
+    /// Caps connections that have not completed LOGON yet. Connections
+    /// beyond the cap are closed right after accept.
+    pub fn max_unauthenticated_connections(mut self, limit: usize) -> Self {
+        self.max_unauthenticated_connections = Some(limit);
+        self
+    }
+
     /// Sets the maximum allowed size for a single Bolt message in bytes.
     ///
     /// Messages exceeding this limit will be rejected with a protocol error.
@@ -130,6 +220,71 @@ impl<B: BoltBackend> BoltServer<B> {
         self
     }
 
+    /// Runs every auto-commit query and transaction in read mode, whatever
+    /// access mode the client asks for. Writes fail with
+    /// `Neo.ClientError.Statement.AccessMode`, from the backend (see
+    /// [`SessionProperty::AccessMode`](crate::server::SessionProperty::AccessMode))
+    /// or, if the backend runs them anyway, from the server once the result
+    /// summary reports a write.
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Runs each auto-commit query in read mode in an explicit transaction,
+    /// committed only if its summary reports no write, so that writes are
+    /// rolled back even by backends that ignore
+    /// [`SessionProperty::AccessMode`](crate::server::SessionProperty::AccessMode).
+    /// Costs a BEGIN and a COMMIT per read, and fails statements that cannot
+    /// run in an explicit transaction, such as `CALL { ... } IN TRANSACTIONS`.
+    /// Default: off, leaving read-mode writes of such backends committed
+    /// before the query fails.
+    pub fn transactional_reads(mut self, enabled: bool) -> Self {
+        self.transactional_reads = enabled;
+        self
+    }
+
+    /// Advertises `connection.recv_timeout_seconds` (whole seconds, at
+    /// least 1) in the HELLO hints: clients may treat the connection as
+    /// broken after that long without a message. While a query, a BEGIN
+    /// (including its bookmark wait) or a commit runs, the server sends a
+    /// NOOP chunk every third of the timeout so that slow requests do not
+    /// look like dead connections.
+    pub fn connection_recv_timeout(mut self, timeout: Duration) -> Self {
+        self.recv_timeout = Some(timeout);
+        self
+    }
+
+    /// Enables the PROXY protocol (v1 and v2) for connections from trusted
+    /// load balancers.
+    ///
+    /// The original client address from the header replaces the socket
+    /// address in [`PeerInfo`](crate::server::PeerInfo), session tracking
+    /// and logs. See [`ProxyProtocol`] for how untrusted sources are treated.
+    pub fn proxy_protocol(mut self, config: ProxyProtocol) -> Self {
+        self.proxy_protocol = Some(config);
+        self
+    }
+
+    /// Sets how long shutdown waits for in-flight work before closing connections.
+    ///
+    /// On shutdown the server stops accepting connections and drains: running
+    /// queries finish and their results can still be pulled, transactions
+    /// that were running a query may run further queries and commit, and new
+    /// requests get a transient `Neo.TransientError.General.DatabaseUnavailable`
+    /// failure so drivers retry elsewhere. Transactions idle when the drain
+    /// starts are rolled back at once, and their next request fails the same
+    /// way. Once nothing is in flight, or the grace period
+    /// ends, every connection is closed, transactions still open are rolled
+    /// back, and each session is passed to
+    /// [`close_session`](BoltBackend::close_session).
+    ///
+    /// Default: zero (close connections immediately).
+    pub fn shutdown_grace_period(mut self, grace: Duration) -> Self {
+        self.shutdown_grace_period = grace;
+        self
+    }
+
     /// Sets a shutdown signal future.
     pub fn shutdown(mut self, signal: impl Future<Output = ()> + Send + 'static) -> Self {
         self.shutdown = Some(Box::pin(signal));
@@ -137,102 +292,20 @@ impl<B: BoltBackend> BoltServer<B> {
     }
 
     /// Starts the Bolt server, listening for TCP connections on `addr`.
-    #[allow(clippy::clone_on_copy)] // tls_acceptor is Option<Arc<..>> with tls, Option<()> without
+    ///
+    /// Runs until the shutdown signal fires. Use [`bind`](Self::bind) to get a
+    /// [`ServerHandle`] instead.
     pub async fn serve(self, addr: SocketAddr) -> Result<(), BoltError> {
-        let listener = TcpListener::bind(addr).await?;
-        let backend = Arc::new(self.backend);
-        let session_manager = Arc::new(SessionManager::new(self.max_sessions));
-        let auth_validator = self.auth_validator;
-
-        #[cfg(feature = "tls")]
-        let tls_acceptor = self.tls_config.map(|c| Arc::new(c.acceptor));
-        #[cfg(not(feature = "tls"))]
-        let tls_acceptor: Option<()> = None;
-
-        // Idle session reaper.
-        let reaper_handle = if let Some(timeout) = self.idle_timeout {
-            let sm = session_manager.clone();
-            let be = backend.clone();
-            let handle = tokio::spawn(async move {
-                let mut interval = tokio::time::interval(timeout / 2);
-                loop {
-                    interval.tick().await;
-                    let expired = sm.reap_idle(timeout);
-                    for id in &expired {
-                        let handle = crate::server::SessionHandle(id.clone());
-                        let _ = be.close_session(&handle).await;
-                        tracing::debug!(session_id = %id, "reaped idle Bolt session");
-                    }
-                }
-            });
-            Some(handle)
-        } else {
-            None
-        };
-
-        let tls_label = if tls_acceptor.is_some() { " (TLS)" } else { "" };
-        tracing::info!(%addr, "Bolt server listening{}", tls_label);
-
-        // Accept loop.
-        let shutdown = self.shutdown;
-        let max_message_size = self.max_message_size;
-        let accept_result = if let Some(shutdown_signal) = shutdown {
-            tokio::pin!(shutdown_signal);
-            loop {
-                tokio::select! {
-                    result = listener.accept() => {
-                        match result {
-                            Ok((stream, peer_addr)) => {
-                                spawn_connection(
-                                    stream,
-                                    peer_addr,
-                                    backend.clone(),
-                                    session_manager.clone(),
-                                    auth_validator.clone(),
-                                    tls_acceptor.clone(),
-                                    max_message_size,
-                                );
-                            }
-                            Err(e) => {
-                                tracing::warn!(error = %e, "accept error");
-                            }
-                        }
-                    }
-                    () = &mut shutdown_signal => {
-                        tracing::info!("Bolt server shutting down");
-                        break;
-                    }
-                }
-            }
-            Ok(())
-        } else {
-            loop {
-                match listener.accept().await {
-                    Ok((stream, peer_addr)) => {
-                        spawn_connection(
-                            stream,
-                            peer_addr,
-                            backend.clone(),
-                            session_manager.clone(),
-                            auth_validator.clone(),
-                            tls_acceptor.clone(),
-                            max_message_size,
-                        );
-                    }
-                    Err(e) => {
-                        tracing::warn!(error = %e, "accept error");
-                    }
-                }
-            }
-        };
-
-        // Stop reaper.
-        if let Some(handle) = reaper_handle {
-            handle.abort();
-        }
+        self.bind(addr).await?.wait().await
+    }
 
-        tracing::info!("Bolt server stopped");
-        accept_result
+    /// Binds a TCP listener on `addr` and starts serving in a background task.
+    ///
+    /// Returns once the listener is bound. The returned [`ServerHandle`]
+    /// reports the actual bound address (when binding port 0), live stats,
+    /// and can shut the server down from any task.
+    pub async fn bind(self, addr: SocketAddr) -> Result<ServerHandle, BoltError> {
+        self.bind_transport(addr, Transport::Bolt).await
     }
 
     /// Starts the Bolt server, listening for WebSocket connections on `addr`.
@@ -244,242 +317,274 @@ impl<B: BoltBackend> BoltServer<B> {
     /// When the `tls` feature is also enabled, connections are TLS-wrapped
     /// before the WebSocket upgrade (WSS).
     #[cfg(feature = "ws")]
-    #[allow(clippy::clone_on_copy)] // tls_acceptor is Option<Arc<..>> with tls, Option<()> without
     pub async fn ws_serve(self, addr: SocketAddr) -> Result<(), BoltError> {
+        self.ws_bind(addr).await?.wait().await
+    }
+
+    /// Binds a WebSocket listener on `addr` and starts serving in a background task.
+    ///
+    /// The WebSocket counterpart of [`bind`](Self::bind).
+    #[cfg(feature = "ws")]
+    pub async fn ws_bind(self, addr: SocketAddr) -> Result<ServerHandle, BoltError> {
+        self.bind_transport(addr, Transport::WebSocket).await
+    }
+
+    /// Serves raw Bolt, Bolt over WebSocket and plain HTTP on a single port.
+    ///
+    /// The protocol of each connection is detected from its first bytes
+    /// (see [`sniff`](crate::server::sniff)); plain HTTP requests go to the
+    /// [`http_handler`](Self::http_handler). With TLS configured,
+    /// connections that start with a TLS handshake are decrypted and
+    /// detection runs again on the plaintext, so TLS and plaintext clients
+    /// share the port.
+    #[cfg(feature = "ws")]
+    pub async fn serve_auto(self, addr: SocketAddr) -> Result<(), BoltError> {
+        self.bind_auto(addr).await?.wait().await
+    }
+
+    /// Binds a protocol-detecting listener on `addr` and starts serving in a
+    /// background task.
+    ///
+    /// The single-port counterpart of [`bind`](Self::bind); see
+    /// [`serve_auto`](Self::serve_auto).
+    #[cfg(feature = "ws")]
+    pub async fn bind_auto(self, addr: SocketAddr) -> Result<ServerHandle, BoltError> {
+        self.bind_transport(addr, Transport::Auto).await
+    }
+
+    /// Serves connections from any [`BoltListener`], e.g. a Unix domain
+    /// socket, a socket-activated listener, or connections handed off by
+    /// your own multiplexer.
+    ///
+    /// TLS, if configured, is applied to every accepted stream. Runs until
+    /// the shutdown signal fires.
+    ///
+    /// ```rust,no_run
+    /// use boltr::server::BoltServer;
+    /// # use boltr::server::BoltBackend;
+    ///
+    /// # #[cfg(unix)]
+    /// # async fn example(backend: impl BoltBackend) -> Result<(), boltr::error::BoltError> {
+    /// let listener = tokio::net::UnixListener::bind("/run/boltr.sock")?;
+    /// BoltServer::builder(backend).serve_listener(listener).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn serve_listener(self, listener: impl BoltListener) -> Result<(), BoltError> {
+        // Only the builder's shutdown future stops this server.
+        let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+        let (ctx, options) = self.into_parts();
+        tracing::info!("Bolt server listening");
+        run_server(
+            ctx,
+            listener,
+            Transport::Bolt,
+            shutdown_signal(options.shutdown, shutdown_rx),
+            options.idle_timeout,
+            options.grace_period,
+            "Bolt server",
+        )
+        .await
+    }
+
+    /// Turns the configuration into a [`BoltService`] that serves individual,
+    /// already-accepted streams via [`BoltService::serve_connection`].
+    ///
+    /// The idle timeout, shutdown signal and grace period only apply to
+    /// servers that own their accept loop and are ignored here.
+    pub fn into_service(self) -> BoltService<B> {
+        BoltService::new(self.into_parts().0)
+    }
+
+    async fn bind_transport(
+        self,
+        addr: SocketAddr,
+        transport: Transport,
+    ) -> Result<ServerHandle, BoltError> {
         let listener = TcpListener::bind(addr).await?;
-        let backend = Arc::new(self.backend);
-        let session_manager = Arc::new(SessionManager::new(self.max_sessions));
-        let auth_validator = self.auth_validator;
+        let local_addr = listener.local_addr()?;
+
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let (ctx, options) = self.into_parts();
 
+        let label = match transport {
+            Transport::Bolt => "Bolt server",
+            #[cfg(feature = "ws")]
+            Transport::WebSocket => "Bolt WebSocket server",
+            #[cfg(feature = "ws")]
+            Transport::Auto => "Bolt multi-protocol server",
+        };
         #[cfg(feature = "tls")]
-        let tls_acceptor = self.tls_config.map(|c| Arc::new(c.acceptor));
+        let tls_label = match (ctx.tls_acceptor.is_some(), transport) {
+            (false, _) => "",
+            (true, Transport::Bolt) => " (TLS)",
+            #[cfg(feature = "ws")]
+            (true, Transport::WebSocket) => " (WSS)",
+            #[cfg(feature = "ws")]
+            (true, Transport::Auto) => " (TLS)",
+        };
         #[cfg(not(feature = "tls"))]
-        let tls_acceptor: Option<()> = None;
-
-        // Idle session reaper.
-        let reaper_handle = if let Some(timeout) = self.idle_timeout {
-            let sm = session_manager.clone();
-            let be = backend.clone();
-            let handle = tokio::spawn(async move {
-                let mut interval = tokio::time::interval(timeout / 2);
-                loop {
-                    interval.tick().await;
-                    let expired = sm.reap_idle(timeout);
-                    for id in &expired {
-                        let handle = crate::server::SessionHandle(id.clone());
-                        let _ = be.close_session(&handle).await;
-                        tracing::debug!(session_id = %id, "reaped idle Bolt session");
-                    }
-                }
-            });
-            Some(handle)
-        } else {
-            None
+        let tls_label = "";
+        tracing::info!(addr = %local_addr, "{label} listening{tls_label}");
+
+        let task = tokio::spawn(run_server(
+            ctx.clone(),
+            listener,
+            transport,
+            shutdown_signal(options.shutdown, shutdown_rx),
+            options.idle_timeout,
+            options.grace_period,
+            label,
+        ));
+
+        Ok(ServerHandle::new(
+            local_addr,
+            shutdown_tx,
+            task,
+            ctx.session_manager.clone(),
+            ctx.counters.clone(),
+        ))
+    }
+
+    /// Splits the builder into the state shared by connections and the
+    /// settings of the accept loop.
+    fn into_parts(self) -> (Arc<ServerContext<B>>, ServeOptions) {
+        let mut session_manager = SessionManager::new(self.max_sessions);
+        if let Some(limit) = self.max_sessions_per_ip {
+            session_manager = session_manager.with_max_per_ip(limit);
+        }
+        if let Some(limit) = self.max_sessions_per_principal {
+            session_manager = session_manager.with_max_per_principal(limit);
+        }
+        let mut ctx = ServerContext::new(Arc::new(self.backend), Arc::new(session_manager));
+        ctx.auth_validator = self.auth_validator;
+        ctx.authorizer = self.authorizer;
+        ctx.bookmark_manager = self.bookmark_manager;
+        ctx.max_message_size = self.max_message_size;
+        ctx.read_only = self.read_only;
+        ctx.transactional_reads = self.transactional_reads;
+        ctx.recv_timeout = self.recv_timeout;
+        ctx.proxy_protocol = self.proxy_protocol;
+        ctx.timeouts = self.timeouts;
+        ctx.unauthenticated_limit = self
+            .max_unauthenticated_connections
+            .map(|limit| Arc::new(Semaphore::new(limit)));
+        ctx.rate_limiter = self.rate_limit.map(RateLimiter::new);
+        ctx.auth_lockout = self.auth_lockout.map(LockoutTracker::new);
+        #[cfg(feature = "tls")]
+        {
+            ctx.tls_acceptor = self.tls_config.map(|c| c.acceptor);
+        }
+        #[cfg(feature = "ws")]
+        {
+            ctx.http_handler = self.http_handler;
+        }
+        let options = ServeOptions {
+            idle_timeout: self.idle_timeout,
+            grace_period: self.shutdown_grace_period,
+            shutdown: self.shutdown,
         };
+        (Arc::new(ctx), options)
+    }
+}
 
-        let tls_label = if tls_acceptor.is_some() { " (WSS)" } else { "" };
-        tracing::info!(%addr, "Bolt WebSocket server listening{}", tls_label);
+/// Accept-loop settings that are not part of the per-connection context.
+struct ServeOptions {
+    idle_timeout: Option<Duration>,
+    grace_period: Duration,
+    shutdown: Option<Pin<Box<dyn Future<Output = ()> + Send>>>,
+}
 
-        // Accept loop.
-        let shutdown = self.shutdown;
-        let max_message_size = self.max_message_size;
-        let accept_result = if let Some(shutdown_signal) = shutdown {
-            tokio::pin!(shutdown_signal);
-            loop {
-                tokio::select! {
-                    result = listener.accept() => {
-                        match result {
-                            Ok((stream, peer_addr)) => {
-                                spawn_ws_connection(
-                                    stream,
-                                    peer_addr,
-                                    backend.clone(),
-                                    session_manager.clone(),
-                                    auth_validator.clone(),
-                                    tls_acceptor.clone(),
-                                    max_message_size,
-                                );
-                            }
-                            Err(e) => {
-                                tracing::warn!(error = %e, "accept error");
-                            }
-                        }
-                    }
-                    () = &mut shutdown_signal => {
-                        tracing::info!("Bolt WebSocket server shutting down");
-                        break;
-                    }
-                }
-            }
-            Ok(())
-        } else {
-            loop {
-                match listener.accept().await {
-                    Ok((stream, peer_addr)) => {
-                        spawn_ws_connection(
-                            stream,
-                            peer_addr,
-                            backend.clone(),
-                            session_manager.clone(),
-                            auth_validator.clone(),
-                            tls_acceptor.clone(),
-                            max_message_size,
-                        );
-                    }
-                    Err(e) => {
-                        tracing::warn!(error = %e, "accept error");
-                    }
-                }
+/// Resolves when either the builder's shutdown future or the handle's
+/// shutdown signal fires. Dropping the handle does not trigger shutdown.
+async fn shutdown_signal(
+    user_shutdown: Option<Pin<Box<dyn Future<Output = ()> + Send>>>,
+    mut handle_shutdown: watch::Receiver<bool>,
+) {
+    let handle_signal = async move {
+        if handle_shutdown.wait_for(|stop| *stop).await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    };
+    match user_shutdown {
+        Some(user_signal) => {
+            tokio::select! {
+                () = user_signal => {}
+                () = handle_signal => {}
             }
-        };
-
-        // Stop reaper.
-        if let Some(handle) = reaper_handle {
-            handle.abort();
         }
-
-        tracing::info!("Bolt WebSocket server stopped");
-        accept_result
+        None => handle_signal.await,
     }
 }
 
-fn spawn_connection<B: BoltBackend>(
-    stream: tokio::net::TcpStream,
-    peer_addr: SocketAddr,
-    backend: Arc<B>,
-    session_manager: Arc<SessionManager>,
-    auth_validator: Option<Arc<dyn AuthValidator>>,
-    #[cfg(feature = "tls")] tls_acceptor: Option<Arc<TlsAcceptor>>,
-    #[cfg(not(feature = "tls"))] _tls_acceptor: Option<()>,
-    max_message_size: Option<usize>,
-) {
-    tokio::spawn(async move {
-        #[cfg(feature = "tls")]
-        if let Some(acceptor) = tls_acceptor {
-            match acceptor.accept(stream).await {
-                Ok(tls_stream) => {
-                    run_handshake_and_connection(
-                        tls_stream,
-                        peer_addr,
-                        backend,
-                        session_manager,
-                        auth_validator,
-                        max_message_size,
-                    )
-                    .await;
-                }
-                Err(e) => {
-                    tracing::debug!(%peer_addr, error = %e, "TLS handshake failed");
+async fn run_server<B: BoltBackend, L: BoltListener>(
+    ctx: Arc<ServerContext<B>>,
+    mut listener: L,
+    transport: Transport,
+    shutdown: impl Future<Output = ()>,
+    idle_timeout: Option<Duration>,
+    grace_period: Duration,
+    label: &'static str,
+) -> Result<(), BoltError> {
+    // Idle session reaper.
+    let reaper_handle = idle_timeout.map(|timeout| {
+        let sm = ctx.session_manager.clone();
+        let be = ctx.backend.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(timeout / 2);
+            loop {
+                interval.tick().await;
+                let expired = sm.reap_idle(timeout);
+                for id in &expired {
+                    let handle = crate::server::SessionHandle(id.clone());
+                    let _ = be.close_session(&handle).await;
+                    tracing::debug!(session_id = %id, "reaped idle Bolt session");
                 }
             }
-            return;
-        }
-
-        run_handshake_and_connection(
-            stream,
-            peer_addr,
-            backend,
-            session_manager,
-            auth_validator,
-            max_message_size,
-        )
-        .await;
+        })
     });
-}
 
-#[cfg(feature = "ws")]
-fn spawn_ws_connection<B: BoltBackend>(
-    stream: tokio::net::TcpStream,
-    peer_addr: SocketAddr,
-    backend: Arc<B>,
-    session_manager: Arc<SessionManager>,
-    auth_validator: Option<Arc<dyn AuthValidator>>,
-    #[cfg(feature = "tls")] tls_acceptor: Option<Arc<TlsAcceptor>>,
-    #[cfg(not(feature = "tls"))] _tls_acceptor: Option<()>,
-    max_message_size: Option<usize>,
-) {
-    tokio::spawn(async move {
-        #[cfg(feature = "tls")]
-        if let Some(acceptor) = tls_acceptor {
-            match acceptor.accept(stream).await {
-                Ok(tls_stream) => match tokio_tungstenite::accept_async(tls_stream).await {
-                    Ok(ws_stream) => {
-                        let adapted = crate::ws::WsStream::new(ws_stream);
-                        run_handshake_and_connection(
-                            adapted,
-                            peer_addr,
-                            backend,
-                            session_manager,
-                            auth_validator,
-                            max_message_size,
-                        )
-                        .await;
+    // Accept loop.
+    let mut connections = JoinSet::new();
+    tokio::pin!(shutdown);
+    loop {
+        tokio::select! {
+            result = listener.accept() => {
+                match result {
+                    Ok((stream, peer)) => {
+                        ctx.counters.connection_accepted();
+                        connections.spawn(ctx.clone().serve_stream(stream, peer, transport));
                     }
                     Err(e) => {
-                        tracing::debug!(%peer_addr, error = %e, "WebSocket upgrade failed");
+                        tracing::warn!(error = %e, "accept error");
                     }
-                },
-                Err(e) => {
-                    tracing::debug!(%peer_addr, error = %e, "TLS handshake failed");
                 }
             }
-            return;
-        }
-
-        match tokio_tungstenite::accept_async(stream).await {
-            Ok(ws_stream) => {
-                let adapted = crate::ws::WsStream::new(ws_stream);
-                run_handshake_and_connection(
-                    adapted,
-                    peer_addr,
-                    backend,
-                    session_manager,
-                    auth_validator,
-                    max_message_size,
-                )
-                .await;
-            }
-            Err(e) => {
-                tracing::debug!(%peer_addr, error = %e, "WebSocket upgrade failed");
+            Some(_) = connections.join_next(), if !connections.is_empty() => {}
+            () = &mut shutdown => {
+                tracing::info!("{label} shutting down");
+                break;
             }
         }
-    });
-}
+    }
+    drop(listener);
+
+    // Drain: let in-flight work finish, then close every connection.
+    ctx.drain.begin();
+    if tokio::time::timeout(grace_period, ctx.drain.idle())
+        .await
+        .is_err()
+    {
+        tracing::info!("{label} grace period elapsed, closing connections");
+    }
+    ctx.drain.close();
+    while connections.join_next().await.is_some() {}
 
-pub(crate) async fn run_handshake_and_connection<S, B>(
-    stream: S,
-    peer_addr: SocketAddr,
-    backend: Arc<B>,
-    session_manager: Arc<SessionManager>,
-    auth_validator: Option<Arc<dyn AuthValidator>>,
-    max_message_size: Option<usize>,
-) where
-    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send + 'static,
-    B: BoltBackend,
-{
-    let (read_half, write_half) = tokio::io::split(stream);
-
-    // Perform handshake on the raw stream, then split for the connection.
-    let mut combined = read_half.unsplit(write_half);
-    match server_handshake(&mut combined).await {
-        Ok(version) => {
-            tracing::debug!(%peer_addr, ?version, "Bolt handshake complete");
-            let (rh, wh) = tokio::io::split(combined);
-            let mut conn = Connection::new(
-                rh,
-                wh,
-                backend,
-                session_manager,
-                auth_validator,
-                peer_addr,
-                max_message_size,
-            );
-            if let Err(e) = conn.run().await {
-                tracing::debug!(%peer_addr, error = %e, "Bolt connection closed");
-            }
-        }
-        Err(e) => {
-            tracing::debug!(%peer_addr, error = %e, "Bolt handshake failed");
-        }
+    // Stop reaper.
+    if let Some(handle) = reaper_handle {
+        handle.abort();
     }
+
+    tracing::info!("{label} stopped");
+    Ok(())
 }
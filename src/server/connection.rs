@@ -1,10 +1,14 @@
 //! Per-TCP-connection Bolt handler.
 
+use std::future::Future;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
 use bytes::BytesMut;
 use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::OwnedSemaphorePermit;
+use tokio::time::Instant;
 
 use crate::chunk::{ChunkReader, ChunkWriter};
 use crate::error::BoltError;
@@ -12,14 +16,17 @@ use crate::message::decode::decode_client_message;
 use crate::message::encode::encode_server_message;
 use crate::message::request::ClientMessage;
 use crate::message::response::ServerMessage;
-use crate::server::auth::AuthValidator;
+use crate::server::auth::{AuthInfo, AuthValidator, AuthorizationRequest, Operation};
 use crate::server::backend::{
-    AuthCredentials, BoltBackend, BoltRecord, SessionConfig, SessionHandle, SessionProperty,
-    TransactionHandle,
+    AccessMode, AuthCredentials, BoltBackend, BoltRecord, SessionConfig, SessionHandle,
+    SessionProperty, TransactionHandle, extract_bookmarks,
 };
+use crate::server::context::{ServerContext, sleep_until};
+use crate::server::drain::Phase;
+use crate::server::peer::PeerInfo;
 use crate::server::session_manager::SessionManager;
 use crate::server::state_machine::ConnectionState;
-use crate::types::{BoltDict, BoltValue};
+use crate::types::{BoltDict, BoltValue, QueryType, ResultSummary, millis};
 
 /// Buffered query results waiting for PULL/DISCARD.
 struct PendingResult {
@@ -27,21 +34,59 @@ struct PendingResult {
     offset: usize,
     #[allow(dead_code)]
     columns: Vec<String>,
-    summary: BoltDict,
+    summary: ResultSummary,
+    /// When the result became available, for `t_last`.
+    available_at: Instant,
+}
+
+impl PendingResult {
+    /// The summary for the final PULL or DISCARD, with `t_last` measured
+    /// unless the backend reported it.
+    fn into_summary(self) -> ResultSummary {
+        let mut summary = self.summary;
+        summary
+            .result_consumed_after
+            .get_or_insert_with(|| self.available_at.elapsed());
+        summary
+    }
 }
 
 /// Handles a single Bolt TCP connection.
 pub struct Connection<R, W, B: BoltBackend> {
     reader: ChunkReader<R>,
     writer: ChunkWriter<W>,
-    backend: Arc<B>,
-    session_manager: Arc<SessionManager>,
-    auth_validator: Option<Arc<dyn AuthValidator>>,
+    ctx: Arc<ServerContext<B>>,
     state: ConnectionState,
     session: Option<SessionHandle>,
     transaction: Option<TransactionHandle>,
     pending_result: Option<PendingResult>,
-    peer_addr: SocketAddr,
+    peer: PeerInfo,
+    /// Whether this connection currently counts as busy for graceful drain.
+    busy: bool,
+    /// Whether draining rolled back the transaction while it was idle, in
+    /// which case the next request fails.
+    drain_rolled_back: bool,
+    /// Slot in the unauthenticated-connection cap, released on LOGON.
+    unauthenticated_permit: Option<OwnedSemaphorePermit>,
+    /// Principal authenticated by the last successful LOGON.
+    principal: Option<String>,
+    /// Identity returned by the validator on the last successful LOGON.
+    auth_info: Option<AuthInfo>,
+    /// When the credentials from the last LOGON expire, on the monotonic
+    /// clock.
+    token_deadline: Option<Instant>,
+    /// Whether the connection FAILED because its token expired, in which
+    /// case LOGOFF is accepted so the client can log on again without RESET.
+    token_expired_failure: bool,
+    /// Home database of the principal, resolved on LOGON.
+    home_database: Option<String>,
+    /// Access mode and database of the open transaction.
+    tx_mode: AccessMode,
+    tx_database: Option<String>,
+    /// Whether a query in the open transaction wrote.
+    tx_wrote: bool,
+    /// Access mode last sent to the backend with `configure_session`.
+    backend_mode: AccessMode,
 }
 
 impl<R, W, B> Connection<R, W, B>
@@ -50,6 +95,11 @@ where
     W: AsyncWrite + Unpin,
     B: BoltBackend,
 {
+    /// Creates a handler for a connection whose Bolt handshake is done,
+    /// with default settings for everything the arguments do not cover.
+    #[deprecated(
+        note = "serve connections with `BoltServer` or `BoltService`, which apply every server setting"
+    )]
     pub fn new(
         reader: R,
         writer: W,
@@ -58,50 +108,133 @@ where
         auth_validator: Option<Arc<dyn AuthValidator>>,
         peer_addr: SocketAddr,
         max_message_size: Option<usize>,
+    ) -> Self {
+        let mut ctx = ServerContext::new(backend, session_manager);
+        ctx.auth_validator = auth_validator;
+        ctx.max_message_size = max_message_size;
+        Self::with_context(reader, writer, Arc::new(ctx), peer_addr.into(), None)
+    }
+
+    pub(crate) fn with_context(
+        reader: R,
+        writer: W,
+        ctx: Arc<ServerContext<B>>,
+        peer: PeerInfo,
+        unauthenticated_permit: Option<OwnedSemaphorePermit>,
     ) -> Self {
         let mut chunk_reader = ChunkReader::new(reader);
-        if let Some(max) = max_message_size {
+        if let Some(max) = ctx.max_message_size {
             chunk_reader.set_max_message_size(max);
         }
         Self {
             reader: chunk_reader,
             writer: ChunkWriter::new(writer),
-            backend,
-            session_manager,
-            auth_validator,
+            ctx,
             state: ConnectionState::Negotiation,
             session: None,
             transaction: None,
             pending_result: None,
-            peer_addr,
+            peer,
+            busy: false,
+            drain_rolled_back: false,
+            unauthenticated_permit,
+            principal: None,
+            auth_info: None,
+            token_deadline: None,
+            token_expired_failure: false,
+            home_database: None,
+            tx_mode: AccessMode::Write,
+            tx_database: None,
+            tx_wrote: false,
+            backend_mode: AccessMode::Write,
         }
     }
 
-    /// Runs the connection lifecycle: handshake → message loop → cleanup.
+    /// Runs the connection lifecycle: message loop → cleanup.
+    ///
+    /// The handshake is done externally before constructing the connection,
+    /// so this starts in Negotiation state waiting for HELLO. Cleanup rolls
+    /// back any open transaction and closes the session however the loop ends.
     pub async fn run(&mut self) -> Result<(), BoltError> {
-        // Step 1: Handshake (reads magic + versions from the raw stream).
-        // Handshake is done externally before constructing Connection, so we
-        // start in Negotiation state waiting for HELLO.
+        let result = self.message_loop().await;
+
+        // Cleanup.
+        self.set_busy(false);
+        if let Some(ref session) = self.session {
+            if let Some(tx) = self.transaction.take() {
+                let _ = self.ctx.backend.rollback(session, &tx).await;
+            }
+            self.ctx.session_manager.remove(&session.0);
+            let _ = self.ctx.backend.close_session(session).await;
+        }
 
-        // Step 2: Message loop.
+        result
+    }
+
+    async fn message_loop(&mut self) -> Result<(), BoltError> {
+        let mut phase = self.ctx.drain.subscribe();
+        let mut deadline_state = None;
+        let mut deadline = None;
+        let mut phase_changed_while_idle = false;
         loop {
             if self.state == ConnectionState::Defunct {
                 break;
             }
 
-            let msg_bytes = match self.reader.read_message().await {
-                Ok(bytes) => bytes,
-                Err(e) => {
-                    tracing::debug!(%self.peer_addr, error = %e, "read error");
+            // Restart the HELLO / LOGON deadline whenever the state changes.
+            if deadline_state != Some(self.state) {
+                deadline_state = Some(self.state);
+                let timeout = match self.state {
+                    ConnectionState::Negotiation => self.ctx.timeouts.hello,
+                    ConnectionState::Authentication => self.ctx.timeouts.logon,
+                    _ => None,
+                };
+                deadline = timeout.map(|t| Instant::now() + t);
+            }
+
+            let draining = match *phase.borrow_and_update() {
+                Phase::Running => false,
+                Phase::Draining => true,
+                Phase::Closed => break,
+            };
+            // A transaction idle when draining starts is rolled back rather
+            // than holding up shutdown; one that is running may finish.
+            if std::mem::take(&mut phase_changed_while_idle)
+                && draining
+                && self.state == ConnectionState::TxReady
+            {
+                self.roll_back_idle_transaction().await;
+            }
+            self.set_busy(self.transaction.is_some() || self.pending_result.is_some());
+
+            let msg_bytes = tokio::select! {
+                result = self.reader.read_message() => match result {
+                    Ok(bytes) => bytes,
+                    Err(e) => {
+                        tracing::debug!(peer_addr = %self.peer.addr, error = %e, "read error");
+                        break;
+                    }
+                },
+                // Re-evaluate the drain phase; the read is cancel safe.
+                _ = phase.changed() => {
+                    phase_changed_while_idle = true;
+                    continue;
+                }
+                () = sleep_until(deadline) => {
+                    tracing::debug!(
+                        peer_addr = %self.peer.addr,
+                        state = ?self.state,
+                        "timed out waiting for authentication",
+                    );
                     break;
                 }
             };
 
             // Detect if idle reaper closed our session.
             if let Some(ref session) = self.session
-                && !self.session_manager.contains(&session.0)
+                && !self.ctx.session_manager.contains(&session.0)
             {
-                tracing::debug!(%self.peer_addr, "session reaped by idle timeout");
+                tracing::debug!(peer_addr = %self.peer.addr, "session reaped by idle timeout");
                 self.session = None;
                 break;
             }
@@ -114,17 +247,43 @@ where
             let msg = match decode_client_message(&msg_bytes) {
                 Ok(msg) => msg,
                 Err(e) => {
-                    tracing::warn!(%self.peer_addr, error = %e, "decode error");
+                    tracing::warn!(peer_addr = %self.peer.addr, error = %e, "decode error");
                     self.send_failure("Neo.ClientError.Request.InvalidFormat", &e.to_string())
                         .await?;
                     self.state = ConnectionState::Failed;
                     continue;
                 }
             };
+            self.ctx.counters.message_processed();
 
-            if !self.state.accepts(&msg) {
+            if std::mem::take(&mut self.drain_rolled_back)
+                && !matches!(msg, ClientMessage::Reset | ClientMessage::Goodbye)
+            {
+                self.send_failure(
+                    "Neo.TransientError.General.DatabaseUnavailable",
+                    "transaction rolled back: server is shutting down",
+                )
+                .await?;
+                self.state = ConnectionState::Failed;
+                continue;
+            }
+
+            if draining && starts_work(self.state, &msg) {
+                tracing::debug!(peer_addr = %self.peer.addr, "refusing request while draining");
+                self.send_failure(
+                    "Neo.TransientError.General.DatabaseUnavailable",
+                    "server is shutting down",
+                )
+                .await?;
+                self.state = self.state.transition_failure(&msg);
+                continue;
+            }
+
+            let reauthenticating =
+                self.token_expired_failure && matches!(msg, ClientMessage::Logoff);
+            if !self.state.accepts(&msg) && !reauthenticating {
                 tracing::debug!(
-                    %self.peer_addr,
+                    peer_addr = %self.peer.addr,
                     state = ?self.state,
                     msg = ?std::mem::discriminant(&msg),
                     "message not allowed in current state",
@@ -137,26 +296,140 @@ where
                 continue;
             }
 
-            let result = self.handle_message(msg.clone()).await;
+            if self.token_expired()
+                && !matches!(
+                    msg,
+                    ClientMessage::Logoff | ClientMessage::Reset | ClientMessage::Goodbye
+                )
+            {
+                tracing::debug!(peer_addr = %self.peer.addr, "authentication token expired");
+                if let (Some(session), Some(tx)) = (&self.session, self.transaction.take()) {
+                    let _ = self.ctx.backend.rollback(session, &tx).await;
+                }
+                self.pending_result = None;
+                let e = BoltError::TokenExpired("authentication token has expired".into());
+                self.send_message(&ServerMessage::Failure {
+                    metadata: e.to_failure_metadata(),
+                })
+                .await?;
+                self.state = ConnectionState::Failed;
+                self.token_expired_failure = true;
+                continue;
+            }
+
+            // Abandon the request if the grace period runs out mid-flight.
+            self.set_busy(true);
+            let mut closed = phase.clone();
+            let result = tokio::select! {
+                result = self.handle_message(msg.clone()) => result,
+                _ = closed.wait_for(|p| *p == Phase::Closed) => break,
+            };
             match result {
                 Ok(()) => {}
                 Err(e) => {
-                    tracing::debug!(%self.peer_addr, error = %e, "handler error");
+                    tracing::debug!(peer_addr = %self.peer.addr, error = %e, "handler error");
                     let meta = e.to_failure_metadata();
                     self.send_message(&ServerMessage::Failure { metadata: meta })
                         .await?;
                     self.state = self.state.transition_failure(&msg);
+                    self.token_expired_failure = matches!(e, BoltError::TokenExpired(_));
                 }
             }
         }
+        Ok(())
+    }
 
-        // Cleanup.
-        if let Some(ref session) = self.session {
-            self.session_manager.remove(&session.0);
-            let _ = self.backend.close_session(session).await;
+    /// Whether the credentials from the last LOGON have expired.
+    fn token_expired(&self) -> bool {
+        self.token_deadline
+            .is_some_and(|deadline| deadline <= Instant::now())
+    }
+
+    /// Summary metadata for the final PULL or DISCARD; auto-commit results
+    /// get a bookmark when a bookmark manager is configured.
+    fn final_summary(&self, pending: PendingResult, auto_commit: bool) -> BoltDict {
+        let mut summary = pending.into_summary();
+        if auto_commit && let Some(ref manager) = self.ctx.bookmark_manager {
+            let database = summary.database.as_deref();
+            let bookmark = if writes(&summary) {
+                manager.commit(database)
+            } else {
+                manager.current(database)
+            };
+            summary
+                .extra
+                .insert("bookmark".into(), BoltValue::String(bookmark));
         }
+        summary.to_metadata()
+    }
 
-        Ok(())
+    /// The database named in a request, or else the principal's home database.
+    fn target_database(&self, extra: &BoltDict) -> Option<String> {
+        extra
+            .get("db")
+            .and_then(|v| v.as_str())
+            .map(String::from)
+            .or_else(|| self.home_database.clone())
+    }
+
+    /// The requested access mode, downgraded to read on read-only servers
+    /// and for read-only principals.
+    fn effective_mode(&self, requested: AccessMode) -> AccessMode {
+        let read_only = self.ctx.read_only
+            || self.auth_info.as_ref().is_some_and(|info| {
+                info.read_only
+                    || self
+                        .ctx
+                        .authorizer
+                        .as_ref()
+                        .is_some_and(|authorizer| authorizer.read_only(info))
+            });
+        if read_only {
+            AccessMode::Read
+        } else {
+            requested
+        }
+    }
+
+    /// Describes a request for the authorizer.
+    fn authorization<'a>(
+        &'a self,
+        operation: Operation,
+        access_mode: AccessMode,
+        database: Option<&'a str>,
+        query: Option<&'a str>,
+    ) -> AuthorizationRequest<'a> {
+        AuthorizationRequest {
+            operation,
+            auth: self.auth_info.as_ref(),
+            access_mode,
+            database,
+            query,
+            peer: &self.peer,
+        }
+    }
+
+    /// Takes a token from the principal's (or remote IP's) request budget.
+    fn check_rate_limit(&self) -> Result<(), BoltError> {
+        let Some(ref limiter) = self.ctx.rate_limiter else {
+            return Ok(());
+        };
+        limiter.acquire(self.principal.as_deref(), self.peer.addr.ip())
+    }
+
+    /// Rolls back the open transaction for draining; the client learns of
+    /// it from the reply to its next request.
+    async fn roll_back_idle_transaction(&mut self) {
+        if let (Some(session), Some(tx)) = (&self.session, self.transaction.take()) {
+            tracing::debug!(peer_addr = %self.peer.addr, "rolling back idle transaction for shutdown");
+            let _ = self.ctx.backend.rollback(session, &tx).await;
+            self.drain_rolled_back = true;
+        }
+    }
+
+    fn set_busy(&mut self, busy: bool) {
+        self.ctx.drain.set_busy(self.busy, busy);
+        self.busy = busy;
     }
 
     async fn handle_message(&mut self, msg: ClientMessage) -> Result<(), BoltError> {
@@ -200,18 +473,31 @@ where
             database: None,
         };
 
-        let session = self.backend.create_session(&config).await?;
-        self.session_manager
-            .register(session.clone(), self.peer_addr)?;
+        let session = self.ctx.backend.create_session(&config).await?;
+        if let Err(e) = self
+            .ctx
+            .session_manager
+            .register(session.clone(), self.peer.addr)
+        {
+            let _ = self.ctx.backend.close_session(&session).await;
+            return Err(e);
+        }
         self.session = Some(session);
 
-        let mut metadata = self.backend.get_server_info().await.unwrap_or_default();
+        let mut metadata = self.ctx.backend.get_server_info().await.unwrap_or_default();
         metadata
             .entry("connection_id".into())
             .or_insert_with(|| BoltValue::String(uuid::Uuid::new_v4().to_string()));
 
         // Indicate authentication is required (Bolt 5.1+).
-        let hints = BoltDict::new();
+        let mut hints = BoltDict::new();
+        if let Some(timeout) = self.ctx.recv_timeout {
+            let seconds = timeout.as_secs().max(1) as i64;
+            hints.insert(
+                "connection.recv_timeout_seconds".into(),
+                BoltValue::Integer(seconds),
+            );
+        }
         metadata.insert("hints".into(), BoltValue::Dict(hints));
 
         self.send_message(&ServerMessage::Success { metadata })
@@ -223,34 +509,73 @@ where
     }
 
     async fn handle_logon(&mut self, auth: &BoltDict) -> Result<(), BoltError> {
-        let auth_info = if let Some(ref validator) = self.auth_validator {
+        let claimed = auth.get("principal").and_then(|v| v.as_str());
+        let ip = self.peer.addr.ip();
+        if let Some(ref lockout) = self.ctx.auth_lockout {
+            lockout.check(claimed, ip)?;
+        }
+
+        let auth_info = if let Some(ref validator) = self.ctx.auth_validator {
             let creds = AuthCredentials {
                 scheme: auth
                     .get("scheme")
                     .and_then(|v| v.as_str())
                     .unwrap_or("none")
                     .to_string(),
-                principal: auth
-                    .get("principal")
-                    .and_then(|v| v.as_str())
-                    .map(String::from),
+                principal: claimed.map(String::from),
                 credentials: auth
                     .get("credentials")
                     .and_then(|v| v.as_str())
                     .map(String::from),
+                peer: self.peer.clone(),
             };
-            Some(validator.validate(&creds).await?)
+            match validator.validate(&creds).await {
+                Ok(info) => Some(info),
+                Err(e) => {
+                    if let Some(ref lockout) = self.ctx.auth_lockout {
+                        lockout.record_failure(claimed, ip);
+                    }
+                    return Err(e);
+                }
+            }
         } else {
             None
         };
+        if let (Some(lockout), Some(claimed)) = (&self.ctx.auth_lockout, claimed) {
+            lockout.record_success(claimed);
+        }
 
         let credentials_expired = auth_info
             .as_ref()
             .is_some_and(|info| info.credentials_expired);
 
+        let principal = auth_info
+            .as_ref()
+            .map(|info| info.principal.clone())
+            .or_else(|| claimed.map(String::from));
+        if let Some(ref session) = self.session {
+            self.ctx
+                .session_manager
+                .set_principal(&session.0, principal.as_deref())?;
+        }
+        self.principal = principal;
+        self.auth_info.clone_from(&auth_info);
+        self.token_deadline = auth_info
+            .as_ref()
+            .and_then(|info| info.expires_at)
+            .map(|at| {
+                let left = at.duration_since(SystemTime::now()).unwrap_or_default();
+                Instant::now() + left
+            });
+
         if let (Some(session), Some(info)) = (&self.session, auth_info) {
-            self.backend.set_session_auth(session, info).await?;
+            self.ctx.backend.set_session_auth(session, info).await?;
         }
+        self.home_database = self
+            .ctx
+            .backend
+            .home_database(self.principal.as_deref())
+            .await?;
 
         let mut metadata = BoltDict::new();
         if credentials_expired {
@@ -262,38 +587,49 @@ where
         self.state = self.state.transition_success(&ClientMessage::Logon {
             auth: BoltDict::new(),
         });
+        self.unauthenticated_permit = None;
         Ok(())
     }
 
     async fn handle_logoff(&mut self) -> Result<(), BoltError> {
         // Clear any in-flight state: abort pending transaction, discard results.
         if let (Some(session), Some(tx)) = (&self.session, self.transaction.take()) {
-            let _ = self.backend.rollback(session, &tx).await;
+            let _ = self.ctx.backend.rollback(session, &tx).await;
         }
         self.pending_result = None;
 
         // Notify the backend that the session is de-authenticated.
+        self.principal = None;
+        self.auth_info = None;
+        self.token_deadline = None;
+        self.token_expired_failure = false;
+        self.home_database = None;
         if let Some(ref session) = self.session {
-            self.backend.reset_session(session).await?;
+            self.ctx.session_manager.set_principal(&session.0, None)?;
+            self.ctx.backend.reset_session(session).await?;
+            self.backend_mode = AccessMode::Write;
         }
 
         self.send_message(&ServerMessage::Success {
             metadata: BoltDict::new(),
         })
         .await?;
-        self.state = self.state.transition_success(&ClientMessage::Logoff);
+        // From READY, or from FAILED after an expired token.
+        self.state = ConnectionState::Authentication;
         Ok(())
     }
 
     async fn handle_reset(&mut self) -> Result<(), BoltError> {
         // Abort any pending transaction.
         if let (Some(session), Some(tx)) = (&self.session, self.transaction.take()) {
-            let _ = self.backend.rollback(session, &tx).await;
+            let _ = self.ctx.backend.rollback(session, &tx).await;
         }
         self.pending_result = None;
+        self.token_expired_failure = false;
 
         if let Some(ref session) = self.session {
-            self.backend.reset_session(session).await?;
+            self.ctx.backend.reset_session(session).await?;
+            self.backend_mode = AccessMode::Write;
         }
 
         self.send_message(&ServerMessage::Success {
@@ -310,24 +646,118 @@ where
         parameters: &BoltDict,
         extra: &BoltDict,
     ) -> Result<(), BoltError> {
+        let started = Instant::now();
+        self.check_rate_limit()?;
         let session = self
             .session
             .as_ref()
             .ok_or_else(|| BoltError::Session("no active session".into()))?;
 
-        // Switch database if requested.
-        if let Some(BoltValue::String(db)) = extra.get("db") {
-            self.backend
-                .configure_session(session, SessionProperty::Database(db.clone()))
+        // Queries in a transaction stay on its database unless they name one.
+        let switch_to = if self.transaction.is_some() {
+            extra.get("db").and_then(|v| v.as_str()).map(String::from)
+        } else {
+            self.target_database(extra)
+        };
+        let (access_mode, database) = if self.transaction.is_some() {
+            (self.tx_mode, self.tx_database.clone())
+        } else {
+            (
+                self.effective_mode(AccessMode::from_extra(extra)),
+                switch_to.clone(),
+            )
+        };
+        if let Some(db) = switch_to {
+            let request =
+                self.authorization(Operation::SwitchDatabase, access_mode, Some(&db), None);
+            self.ctx.authorize(&request).await?;
+            self.ctx
+                .backend
+                .configure_session(session, SessionProperty::Database(db))
                 .await?;
         }
+        let request = self.authorization(
+            Operation::Execute,
+            access_mode,
+            database.as_deref(),
+            Some(query),
+        );
+        self.ctx.authorize(&request).await?;
+        if self.transaction.is_none() && self.backend_mode != access_mode {
+            self.ctx
+                .backend
+                .configure_session(session, SessionProperty::AccessMode(access_mode))
+                .await?;
+            self.backend_mode = access_mode;
+        }
 
-        self.session_manager.touch(&session.0);
+        if self.transaction.is_none()
+            && let Some(ref manager) = self.ctx.bookmark_manager
+        {
+            let bookmarks = extract_bookmarks(extra);
+            let wait = manager.wait(&bookmarks);
+            keep_alive(&mut self.writer, self.ctx.recv_timeout, wait).await??;
+        }
 
-        let result = self
+        self.ctx.session_manager.touch(&session.0);
+
+        // Read mode holds even if the backend ignores the access mode: a
+        // read that wrote fails and, with `transactional_reads`, an
+        // auto-commit read runs in a transaction that is only committed if
+        // it did not write.
+        let implicit = if self.ctx.transactional_reads
+            && self.transaction.is_none()
+            && access_mode == AccessMode::Read
+        {
+            let begin_extra = begin_extra(extra);
+            let begin = self.ctx.backend.begin_transaction(session, &begin_extra);
+            Some(keep_alive(&mut self.writer, self.ctx.recv_timeout, begin).await??)
+        } else {
+            None
+        };
+        let transaction = self.transaction.as_ref().or(implicit.as_ref());
+        let execute = self
+            .ctx
             .backend
-            .execute(session, query, parameters, extra, self.transaction.as_ref())
-            .await?;
+            .execute(session, query, parameters, extra, transaction);
+        let mut executed = keep_alive(&mut self.writer, self.ctx.recv_timeout, execute)
+            .await
+            .and_then(|executed| executed)
+            .and_then(|result| {
+                if access_mode == AccessMode::Read && writes(&result.summary) {
+                    Err(BoltError::AccessMode(
+                        "writing in read access mode not allowed".into(),
+                    ))
+                } else {
+                    Ok(result)
+                }
+            });
+        if let Some(tx) = implicit {
+            if let Ok(result) = &mut executed {
+                let commit = self.ctx.backend.commit(session, &tx);
+                let metadata =
+                    keep_alive(&mut self.writer, self.ctx.recv_timeout, commit).await??;
+                for (key, value) in metadata {
+                    result.summary.extra.entry(key).or_insert(value);
+                }
+            } else {
+                let rollback = self.ctx.backend.rollback(session, &tx);
+                let _ = keep_alive(&mut self.writer, self.ctx.recv_timeout, rollback).await;
+            }
+        }
+        let mut result = executed?;
+        let available_at = Instant::now();
+        let t_first = result
+            .summary
+            .result_available_after
+            .take()
+            .unwrap_or(available_at - started);
+        if result.summary.database.is_none() {
+            result.summary.database.clone_from(&database);
+        }
+        if self.transaction.is_some() {
+            self.tx_wrote |= writes(&result.summary);
+        }
 
         // Buffer results for PULL.
         let columns = result.metadata.columns.clone();
@@ -336,6 +766,7 @@ where
             offset: 0,
             columns: columns.clone(),
             summary: result.summary,
+            available_at,
         });
 
         let mut meta = BoltDict::new();
@@ -343,7 +774,10 @@ where
             "fields".into(),
             BoltValue::List(columns.into_iter().map(BoltValue::String).collect()),
         );
-        meta.insert("t_first".into(), BoltValue::Integer(0));
+        meta.insert("t_first".into(), millis(t_first));
+        if let Some(db) = database {
+            meta.insert("db".into(), BoltValue::String(db));
+        }
 
         self.send_message(&ServerMessage::Success { metadata: meta })
             .await?;
@@ -404,7 +838,8 @@ where
         if !has_more {
             // Include summary metadata.
             if let Some(pending) = self.pending_result.take() {
-                meta.extend(pending.summary);
+                let auto_commit = self.state == ConnectionState::Streaming;
+                meta.extend(self.final_summary(pending, auto_commit));
             }
             self.state = self.state.complete_streaming();
         }
@@ -415,36 +850,71 @@ where
     }
 
     async fn handle_discard(&mut self, _extra: &BoltDict) -> Result<(), BoltError> {
-        self.pending_result = None;
+        let pending = self.pending_result.take();
+        let auto_commit = self.state == ConnectionState::Streaming;
         self.state = self.state.complete_streaming();
 
-        self.send_message(&ServerMessage::Success {
-            metadata: BoltDict::from([("has_more".into(), BoltValue::Boolean(false))]),
-        })
-        .await?;
+        let mut metadata = BoltDict::from([("has_more".into(), BoltValue::Boolean(false))]);
+        if let Some(pending) = pending {
+            metadata.extend(self.final_summary(pending, auto_commit));
+        }
+        self.send_message(&ServerMessage::Success { metadata })
+            .await?;
         Ok(())
     }
 
     async fn handle_begin(&mut self, extra: &BoltDict) -> Result<(), BoltError> {
+        self.check_rate_limit()?;
         let session = self
             .session
             .as_ref()
             .ok_or_else(|| BoltError::Session("no active session".into()))?;
 
-        // Switch database if requested.
-        if let Some(BoltValue::String(db)) = extra.get("db") {
-            self.backend
+        let access_mode = self.effective_mode(AccessMode::from_extra(extra));
+        let database = self.target_database(extra);
+        if let Some(ref db) = database {
+            let request =
+                self.authorization(Operation::SwitchDatabase, access_mode, Some(db), None);
+            self.ctx.authorize(&request).await?;
+            self.ctx
+                .backend
                 .configure_session(session, SessionProperty::Database(db.clone()))
                 .await?;
         }
+        let request = self.authorization(
+            Operation::BeginTransaction,
+            access_mode,
+            database.as_deref(),
+            None,
+        );
+        self.ctx.authorize(&request).await?;
+        if self.backend_mode != access_mode {
+            self.ctx
+                .backend
+                .configure_session(session, SessionProperty::AccessMode(access_mode))
+                .await?;
+            self.backend_mode = access_mode;
+        }
+
+        if let Some(ref manager) = self.ctx.bookmark_manager {
+            let bookmarks = extract_bookmarks(extra);
+            let wait = manager.wait(&bookmarks);
+            keep_alive(&mut self.writer, self.ctx.recv_timeout, wait).await??;
+        }
 
-        let tx = self.backend.begin_transaction(session, extra).await?;
+        let begin = self.ctx.backend.begin_transaction(session, extra);
+        let tx = keep_alive(&mut self.writer, self.ctx.recv_timeout, begin).await??;
         self.transaction = Some(tx);
+        self.tx_mode = access_mode;
+        self.tx_wrote = false;
 
-        self.send_message(&ServerMessage::Success {
-            metadata: BoltDict::new(),
-        })
-        .await?;
+        let mut metadata = BoltDict::new();
+        if let Some(ref db) = database {
+            metadata.insert("db".into(), BoltValue::String(db.clone()));
+        }
+        self.tx_database = database;
+        self.send_message(&ServerMessage::Success { metadata })
+            .await?;
         self.state = self.state.transition_success(&ClientMessage::Begin {
             extra: BoltDict::new(),
         });
@@ -461,7 +931,17 @@ where
             .take()
             .ok_or_else(|| BoltError::Transaction("no active transaction".into()))?;
 
-        let metadata = self.backend.commit(session, &tx).await?;
+        let commit = self.ctx.backend.commit(session, &tx);
+        let mut metadata = keep_alive(&mut self.writer, self.ctx.recv_timeout, commit).await??;
+        if let Some(ref manager) = self.ctx.bookmark_manager {
+            let database = self.tx_database.as_deref();
+            let bookmark = if self.tx_wrote {
+                manager.commit(database)
+            } else {
+                manager.current(database)
+            };
+            metadata.insert("bookmark".into(), BoltValue::String(bookmark));
+        }
 
         self.send_message(&ServerMessage::Success { metadata })
             .await?;
@@ -479,7 +959,7 @@ where
             .take()
             .ok_or_else(|| BoltError::Transaction("no active transaction".into()))?;
 
-        self.backend.rollback(session, &tx).await?;
+        self.ctx.backend.rollback(session, &tx).await?;
 
         self.send_message(&ServerMessage::Success {
             metadata: BoltDict::new(),
@@ -495,9 +975,13 @@ where
         bookmarks: &[String],
         extra: &BoltDict,
     ) -> Result<(), BoltError> {
-        let db = extra.get("db").and_then(|v| v.as_str());
+        let db = self.target_database(extra);
 
-        let table = self.backend.route(routing, bookmarks, db).await?;
+        let table = self
+            .ctx
+            .backend
+            .route(routing, bookmarks, db.as_deref())
+            .await?;
 
         let servers: Vec<BoltValue> = table
             .servers
@@ -572,3 +1056,432 @@ where
         self.send_message(&ServerMessage::Ignored).await
     }
 }
+
+/// The entries of a RUN's `extra` that also apply to a BEGIN.
+fn begin_extra(extra: &BoltDict) -> BoltDict {
+    const BEGIN_KEYS: [&str; 5] = ["db", "mode", "tx_timeout", "tx_metadata", "imp_user"];
+    extra
+        .iter()
+        .filter(|(key, _)| BEGIN_KEYS.contains(&key.as_str()))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect()
+}
+
+/// Whether a result reports changes, so committing it moves the bookmark.
+fn writes(summary: &ResultSummary) -> bool {
+    summary.counters.contains_updates()
+        || summary.counters.contains_system_updates()
+        || matches!(
+            summary.query_type,
+            Some(QueryType::Write | QueryType::ReadWrite | QueryType::Schema)
+        )
+}
+
+/// Requests refused while the server drains: anything that would start new
+/// work. Queries inside a transaction that was running when draining began
+/// may still run so that it can commit.
+fn starts_work(state: ConnectionState, msg: &ClientMessage) -> bool {
+    match msg {
+        ClientMessage::Run { .. } => state != ConnectionState::TxReady,
+        ClientMessage::Hello { .. }
+        | ClientMessage::Logon { .. }
+        | ClientMessage::Begin { .. }
+        | ClientMessage::Route { .. } => true,
+        _ => false,
+    }
+}
+
+/// Awaits `request`, writing a NOOP to `writer` every third of
+/// `recv_timeout` meanwhile so that the client does not give up on the
+/// connection.
+async fn keep_alive<W, T>(
+    writer: &mut ChunkWriter<W>,
+    recv_timeout: Option<Duration>,
+    request: impl Future<Output = T>,
+) -> Result<T, BoltError>
+where
+    W: AsyncWrite + Unpin,
+{
+    let Some(recv_timeout) = recv_timeout else {
+        return Ok(request.await);
+    };
+    let period = (recv_timeout / 3).max(Duration::from_millis(1));
+    let mut ticks = tokio::time::interval_at(Instant::now() + period, period);
+    tokio::pin!(request);
+    loop {
+        tokio::select! {
+            output = &mut request => return Ok(output),
+            _ = ticks.tick() => {
+                writer.write_noop().await?;
+                writer.flush().await?;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::BoltError;
+    use crate::message::{ClientMessage, ServerMessage};
+    use crate::server::test_support::{
+        MockBackend, TestClient, failure_code, hello, logon, pull, run,
+    };
+    use std::sync::Arc;
+    use std::sync::atomic::Ordering;
+    use std::time::{Duration, SystemTime};
+
+    use crate::server::{
+        AuthCredentials, AuthInfo, AuthValidator, BoltServer, BookmarkManager, DatabaseRouter,
+    };
+    use crate::types::{BoltDict, BoltValue};
+
+    const ACCESS_MODE: Option<&str> = Some("Neo.ClientError.Statement.AccessMode");
+
+    #[tokio::test]
+    async fn writes_fail_in_read_mode() {
+        let handle = BoltServer::builder(MockBackend::default())
+            .bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let mut client = TestClient::connect(handle.local_addr()).await.unwrap();
+        client.authenticate().await.unwrap();
+
+        let read = BoltDict::from([("mode".into(), BoltValue::String("r".into()))]);
+        let reply = client
+            .request(ClientMessage::Begin { extra: read })
+            .await
+            .unwrap();
+        assert!(matches!(reply, ServerMessage::Success { .. }));
+        let reply = client.request(run("CREATE ()")).await.unwrap();
+        assert_eq!(failure_code(&reply), ACCESS_MODE);
+        client.request(ClientMessage::Reset).await.unwrap();
+
+        // Auto-commit queries default to write mode.
+        let reply = client.request(run("CREATE ()")).await.unwrap();
+        assert!(matches!(reply, ServerMessage::Success { .. }));
+
+        handle.shutdown();
+        handle.wait().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn read_only_server_forces_read_mode() {
+        let handle = BoltServer::builder(MockBackend::default())
+            .read_only(true)
+            .bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let mut client = TestClient::connect(handle.local_addr()).await.unwrap();
+        client.authenticate().await.unwrap();
+
+        let reply = client.request(run("CREATE ()")).await.unwrap();
+        assert_eq!(failure_code(&reply), ACCESS_MODE);
+        client.request(ClientMessage::Reset).await.unwrap();
+        let reply = client.request(run("RETURN 1")).await.unwrap();
+        assert!(matches!(reply, ServerMessage::Success { .. }));
+
+        handle.shutdown();
+        handle.wait().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn read_mode_holds_when_the_backend_ignores_it() {
+        let mut backend = MockBackend::default();
+        backend.ignores_access_mode = true;
+        let handle = BoltServer::builder(backend.clone())
+            .read_only(true)
+            .transactional_reads(true)
+            .bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let mut client = TestClient::connect(handle.local_addr()).await.unwrap();
+        client.authenticate().await.unwrap();
+
+        // The auto-commit write runs, but is rolled back.
+        let reply = client.request(run("CREATE ()")).await.unwrap();
+        assert_eq!(failure_code(&reply), ACCESS_MODE);
+        assert_eq!(*backend.rolled_back.lock().unwrap(), vec!["s0-tx"]);
+        client.request(ClientMessage::Reset).await.unwrap();
+        let reply = client.request(run("RETURN 1")).await.unwrap();
+        assert!(matches!(reply, ServerMessage::Success { .. }));
+        client.request(pull()).await.unwrap();
+        client.recv().await.unwrap();
+        assert_eq!(backend.rolled_back.lock().unwrap().len(), 1);
+
+        // So is an explicit transaction that wrote.
+        let reply = client
+            .request(ClientMessage::Begin {
+                extra: BoltDict::new(),
+            })
+            .await
+            .unwrap();
+        assert!(matches!(reply, ServerMessage::Success { .. }));
+        let reply = client.request(run("CREATE ()")).await.unwrap();
+        assert_eq!(failure_code(&reply), ACCESS_MODE);
+        let reply = client.request(ClientMessage::Commit).await.unwrap();
+        assert!(matches!(reply, ServerMessage::Ignored));
+        client.request(ClientMessage::Reset).await.unwrap();
+        assert_eq!(backend.rolled_back.lock().unwrap().len(), 2);
+
+        handle.shutdown();
+        handle.wait().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn auto_commit_reads_are_not_wrapped_by_default() {
+        let mut backend = MockBackend::default();
+        backend.ignores_access_mode = true;
+        let service = BoltServer::builder(backend.clone())
+            .read_only(true)
+            .into_service();
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        let peer: std::net::SocketAddr = "127.0.0.1:9999".parse().unwrap();
+        tokio::spawn(async move { service.serve_connection(server, peer).await });
+        let mut client = TestClient::handshake(client).await.unwrap();
+        client.authenticate().await.unwrap();
+
+        // The write still fails, but ran in auto-commit: nothing to roll back.
+        let reply = client.request(run("CREATE ()")).await.unwrap();
+        assert_eq!(failure_code(&reply), ACCESS_MODE);
+        assert!(backend.rolled_back.lock().unwrap().is_empty());
+    }
+
+    /// Issues credentials that expire after 300ms.
+    struct ShortLived;
+
+    #[async_trait::async_trait]
+    impl AuthValidator for ShortLived {
+        async fn validate(&self, _credentials: &AuthCredentials) -> Result<AuthInfo, BoltError> {
+            Ok(AuthInfo {
+                principal: "alice".into(),
+                expires_at: Some(SystemTime::now() + Duration::from_millis(300)),
+                ..AuthInfo::default()
+            })
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn logoff_is_accepted_in_failed_only_after_token_expiry() {
+        let backend = MockBackend::default();
+        let service = BoltServer::builder(backend.clone())
+            .auth(ShortLived)
+            .into_service();
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        let peer: std::net::SocketAddr = "127.0.0.1:9999".parse().unwrap();
+        tokio::spawn(async move { service.serve_connection(server, peer).await });
+        let mut client = TestClient::handshake(client).await.unwrap();
+        client.request(hello()).await.unwrap();
+        let reply = client.request(logon("basic")).await.unwrap();
+        assert!(matches!(reply, ServerMessage::Success { .. }));
+
+        // Any other failure still needs RESET.
+        backend.transient_failures.store(1, Ordering::Relaxed);
+        let reply = client.request(run("RETURN 1")).await.unwrap();
+        assert!(matches!(reply, ServerMessage::Failure { .. }));
+        let reply = client.request(ClientMessage::Logoff).await.unwrap();
+        assert!(matches!(reply, ServerMessage::Ignored));
+        client.request(ClientMessage::Reset).await.unwrap();
+
+        tokio::time::advance(Duration::from_millis(400)).await;
+        let reply = client.request(run("RETURN 1")).await.unwrap();
+        assert_eq!(
+            failure_code(&reply),
+            Some("Neo.ClientError.Security.TokenExpired")
+        );
+        let reply = client.request(ClientMessage::Logoff).await.unwrap();
+        assert!(matches!(reply, ServerMessage::Success { .. }));
+        let reply = client.request(logon("basic")).await.unwrap();
+        assert!(matches!(reply, ServerMessage::Success { .. }));
+        let reply = client.request(run("RETURN 1")).await.unwrap();
+        assert!(matches!(reply, ServerMessage::Success { .. }));
+    }
+
+    #[tokio::test]
+    async fn summaries_report_timing_and_type() {
+        let handle = BoltServer::builder(MockBackend::default())
+            .bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let mut client = TestClient::connect(handle.local_addr()).await.unwrap();
+        client.authenticate().await.unwrap();
+
+        let ServerMessage::Success { metadata } = client.request(run("SLEEP 50")).await.unwrap()
+        else {
+            panic!("RUN failed");
+        };
+        let t_first = metadata.get("t_first").and_then(|v| v.as_int()).unwrap();
+        assert!(t_first >= 50, "t_first = {t_first}");
+
+        let mut reply = client.request(pull()).await.unwrap();
+        while matches!(reply, ServerMessage::Record { .. }) {
+            reply = client.recv().await.unwrap();
+        }
+        let ServerMessage::Success { metadata } = reply else {
+            panic!("PULL failed: {reply:?}");
+        };
+        assert!(metadata.get("t_last").and_then(|v| v.as_int()).is_some());
+        assert_eq!(metadata.get("type").and_then(|v| v.as_str()), Some("r"));
+
+        handle.shutdown();
+        handle.wait().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn bookmarks_are_issued_and_awaited() {
+        let manager =
+            Arc::new(BookmarkManager::new().wait_timeout(std::time::Duration::from_millis(200)));
+        let handle = BoltServer::builder(MockBackend::default())
+            .bookmark_manager(manager.clone())
+            .bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let mut client = TestClient::connect(handle.local_addr()).await.unwrap();
+        client.authenticate().await.unwrap();
+
+        let bookmark = |reply: &ServerMessage| match reply {
+            ServerMessage::Success { metadata } => metadata
+                .get("bookmark")
+                .and_then(|v| v.as_str())
+                .map(String::from),
+            other => panic!("expected SUCCESS, got {other:?}"),
+        };
+        let begin = |bookmark: &str| ClientMessage::Begin {
+            extra: BoltDict::from([(
+                "bookmarks".into(),
+                BoltValue::List(vec![BoltValue::String(bookmark.into())]),
+            )]),
+        };
+
+        client.request(run("CREATE ()")).await.unwrap();
+        let mut reply = client.request(pull()).await.unwrap();
+        while matches!(reply, ServerMessage::Record { .. }) {
+            reply = client.recv().await.unwrap();
+        }
+        assert_eq!(bookmark(&reply).as_deref(), Some("bk:neo4j:1"));
+
+        let reply = client.request(begin("bk:neo4j:2")).await.unwrap();
+        assert_eq!(
+            failure_code(&reply),
+            Some("Neo.TransientError.Transaction.BookmarkTimeout")
+        );
+        client.request(ClientMessage::Reset).await.unwrap();
+        let reply = client.request(begin("tx-1")).await.unwrap();
+        assert_eq!(
+            failure_code(&reply),
+            Some("Neo.ClientError.Transaction.InvalidBookmark")
+        );
+        client.request(ClientMessage::Reset).await.unwrap();
+
+        client.request(begin("bk:neo4j:1")).await.unwrap();
+        client.request(run("CREATE ()")).await.unwrap();
+        client.request(pull()).await.unwrap();
+        client.recv().await.unwrap();
+        let reply = client.request(ClientMessage::Commit).await.unwrap();
+        assert_eq!(bookmark(&reply).as_deref(), Some("bk:neo4j:2"));
+        assert_eq!(manager.position("neo4j"), 2);
+
+        handle.shutdown();
+        handle.wait().await.unwrap();
+    }
+
+    fn db(reply: &ServerMessage) -> Option<&str> {
+        match reply {
+            ServerMessage::Success { metadata } => metadata.get("db").and_then(|v| v.as_str()),
+            other => panic!("expected SUCCESS, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn home_database_is_reported() {
+        let hr = MockBackend::default();
+        let router = DatabaseRouter::new()
+            .database("neo4j", MockBackend::default())
+            .database("hr", hr.clone())
+            .home_database("alice", "hr");
+        let handle = BoltServer::builder(router)
+            .bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let mut client = TestClient::connect(handle.local_addr()).await.unwrap();
+        client.request(hello()).await.unwrap();
+        let logon = ClientMessage::Logon {
+            auth: BoltDict::from([
+                ("scheme".into(), BoltValue::String("basic".into())),
+                ("principal".into(), BoltValue::String("alice".into())),
+                ("credentials".into(), BoltValue::String("secret".into())),
+            ]),
+        };
+        client.request(logon).await.unwrap();
+
+        let reply = client.request(run("RETURN 1")).await.unwrap();
+        assert_eq!(db(&reply), Some("hr"));
+        let mut reply = client.request(pull()).await.unwrap();
+        while matches!(reply, ServerMessage::Record { .. }) {
+            reply = client.recv().await.unwrap();
+        }
+        assert_eq!(db(&reply), Some("hr"));
+        assert_eq!(*hr.queries.lock().unwrap(), ["RETURN 1"]);
+
+        let begin = ClientMessage::Begin {
+            extra: BoltDict::from([("db".into(), BoltValue::String("neo4j".into()))]),
+        };
+        let reply = client.request(begin).await.unwrap();
+        assert_eq!(db(&reply), Some("neo4j"));
+        client.request(ClientMessage::Rollback).await.unwrap();
+
+        let route = ClientMessage::Route {
+            routing: BoltDict::new(),
+            bookmarks: vec![],
+            extra: BoltDict::new(),
+        };
+        let ServerMessage::Success { metadata } = client.request(route).await.unwrap() else {
+            panic!("ROUTE failed");
+        };
+        let Some(BoltValue::Dict(rt)) = metadata.get("rt") else {
+            panic!("no routing table: {metadata:?}");
+        };
+        assert_eq!(rt.get("db").and_then(|v| v.as_str()), Some("hr"));
+
+        // Unknown databases are reported as such.
+        let unknown = ClientMessage::Begin {
+            extra: BoltDict::from([("db".into(), BoltValue::String("nope".into()))]),
+        };
+        let reply = client.request(unknown).await.unwrap();
+        assert_eq!(
+            failure_code(&reply),
+            Some("Neo.ClientError.Database.DatabaseNotFound")
+        );
+
+        handle.shutdown();
+        handle.wait().await.unwrap();
+    }
+
+    #[tokio::test]
+    #[allow(deprecated)]
+    async fn deprecated_constructor_serves_a_handshaken_stream() {
+        let (client, mut server) = tokio::io::duplex(64 * 1024);
+        tokio::spawn(async move {
+            crate::server::handshake::server_handshake(&mut server)
+                .await
+                .unwrap();
+            let (rh, wh) = tokio::io::split(server);
+            let mut conn = super::Connection::new(
+                rh,
+                wh,
+                Arc::new(MockBackend::default()),
+                Arc::new(crate::server::SessionManager::new(None)),
+                None,
+                "127.0.0.1:7687".parse().unwrap(),
+                None,
+            );
+            conn.run().await
+        });
+
+        let mut client = TestClient::handshake(client).await.unwrap();
+        client.authenticate().await.unwrap();
+        let reply = client.request(run("RETURN 1")).await.unwrap();
+        assert!(matches!(reply, ServerMessage::Success { .. }));
+        let reply = client.request(pull()).await.unwrap();
+        assert!(matches!(reply, ServerMessage::Record { .. }), "{reply:?}");
+    }
+}
@@ -0,0 +1,580 @@
+//! Per-server state shared by every connection.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
+
+use crate::error::BoltError;
+use crate::server::auth::{AuthValidator, AuthorizationRequest, Authorizer};
+use crate::server::backend::BoltBackend;
+use crate::server::bookmarks::BookmarkManager;
+use crate::server::connection::Connection;
+use crate::server::drain::Drain;
+use crate::server::handle::ServerCounters;
+use crate::server::handshake::server_handshake;
+use crate::server::limits::{LockoutTracker, RateLimiter};
+use crate::server::peer::PeerInfo;
+use crate::server::proxy::{ProxyProtocol, read_header as read_proxy_header};
+use crate::server::session_manager::SessionManager;
+
+#[cfg(feature = "ws")]
+use crate::server::sniff::{HttpHandler, Protocol, Rewind, detect};
+#[cfg(feature = "tls")]
+use crate::server::tls::peer_certificate;
+#[cfg(feature = "ws")]
+use tokio::io::AsyncWriteExt;
+#[cfg(feature = "tls")]
+use tokio_rustls::TlsAcceptor;
+
+/// Response to plain HTTP requests on an auto-detecting port without an
+/// [`HttpHandler`].
+#[cfg(feature = "ws")]
+const HTTP_NOT_FOUND: &[u8] =
+    b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+
+/// How an accepted byte stream carries Bolt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Transport {
+    /// Raw Bolt framing directly on the stream.
+    Bolt,
+    /// Bolt inside binary WebSocket frames, after an HTTP upgrade.
+    #[cfg(feature = "ws")]
+    WebSocket,
+    /// Raw Bolt, WebSocket or plain HTTP, detected from the first bytes.
+    #[cfg(feature = "ws")]
+    Auto,
+}
+
+impl Transport {
+    /// Whether TLS, like the protocol, is detected per connection.
+    #[cfg(feature = "tls")]
+    fn is_auto(self) -> bool {
+        #[cfg(feature = "ws")]
+        return self == Self::Auto;
+        #[cfg(not(feature = "ws"))]
+        false
+    }
+}
+
+/// Deadlines for clients to get through connection setup.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Timeouts {
+    /// From accept until the Bolt handshake completes (including PROXY
+    /// header, TLS and WebSocket upgrade).
+    pub(crate) handshake: Option<Duration>,
+    /// From the Bolt handshake until HELLO arrives.
+    pub(crate) hello: Option<Duration>,
+    /// From HELLO (or LOGOFF) until LOGON arrives.
+    pub(crate) logon: Option<Duration>,
+}
+
+/// Limits a connection carries until it has authenticated.
+#[derive(Debug, Default)]
+pub(crate) struct Admission {
+    /// When the handshake must be complete.
+    deadline: Option<Instant>,
+    /// Slot in the unauthenticated-connection cap, released on LOGON.
+    pub(crate) permit: Option<OwnedSemaphorePermit>,
+}
+
+/// Sleeps until `deadline`, or forever without one.
+pub(crate) async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Configuration and shared state for all connections of one server.
+pub(crate) struct ServerContext<B: BoltBackend> {
+    pub(crate) backend: Arc<B>,
+    pub(crate) session_manager: Arc<SessionManager>,
+    pub(crate) auth_validator: Option<Arc<dyn AuthValidator>>,
+    pub(crate) authorizer: Option<Arc<dyn Authorizer>>,
+    pub(crate) bookmark_manager: Option<Arc<BookmarkManager>>,
+    pub(crate) max_message_size: Option<usize>,
+    pub(crate) read_only: bool,
+    pub(crate) transactional_reads: bool,
+    /// Advertised as `connection.recv_timeout_seconds`; NOOPs keep
+    /// connections alive during long requests.
+    pub(crate) recv_timeout: Option<Duration>,
+    pub(crate) counters: Arc<ServerCounters>,
+    pub(crate) drain: Drain,
+    pub(crate) proxy_protocol: Option<ProxyProtocol>,
+    pub(crate) timeouts: Timeouts,
+    pub(crate) unauthenticated_limit: Option<Arc<Semaphore>>,
+    pub(crate) rate_limiter: Option<RateLimiter>,
+    pub(crate) auth_lockout: Option<LockoutTracker>,
+    #[cfg(feature = "ws")]
+    pub(crate) http_handler: Option<Arc<dyn HttpHandler>>,
+    #[cfg(feature = "tls")]
+    pub(crate) tls_acceptor: Option<TlsAcceptor>,
+}
+
+impl<B: BoltBackend> ServerContext<B> {
+    pub(crate) fn new(backend: Arc<B>, session_manager: Arc<SessionManager>) -> Self {
+        Self {
+            backend,
+            session_manager,
+            auth_validator: None,
+            authorizer: None,
+            bookmark_manager: None,
+            max_message_size: None,
+            read_only: false,
+            transactional_reads: false,
+            recv_timeout: None,
+            counters: Arc::default(),
+            drain: Drain::default(),
+            proxy_protocol: None,
+            timeouts: Timeouts::default(),
+            unauthenticated_limit: None,
+            rate_limiter: None,
+            auth_lockout: None,
+            #[cfg(feature = "ws")]
+            http_handler: None,
+            #[cfg(feature = "tls")]
+            tls_acceptor: None,
+        }
+    }
+
+    /// Consults the authorizer, if one is configured.
+    pub(crate) async fn authorize(
+        &self,
+        request: &AuthorizationRequest<'_>,
+    ) -> Result<(), BoltError> {
+        match &self.authorizer {
+            Some(authorizer) => authorizer.authorize(request).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Admits a new connection, or returns `None` if the cap on
+    /// unauthenticated connections is reached.
+    pub(crate) fn admit(&self, peer: &PeerInfo) -> Option<Admission> {
+        let permit = match &self.unauthenticated_limit {
+            Some(limit) => match limit.clone().try_acquire_owned() {
+                Ok(permit) => Some(permit),
+                Err(_) => {
+                    tracing::debug!(peer_addr = %peer.addr, "too many unauthenticated connections");
+                    return None;
+                }
+            },
+            None => None,
+        };
+        Some(Admission {
+            deadline: self.timeouts.handshake.map(|t| Instant::now() + t),
+            permit,
+        })
+    }
+
+    /// Resolves when connection setup must be abandoned: the server is
+    /// closing or the handshake deadline has passed.
+    async fn setup_abandoned(&self, admission: &Admission, peer: &PeerInfo) {
+        tokio::select! {
+            () = self.drain.closed() => {}
+            () = sleep_until(admission.deadline) => {
+                tracing::debug!(peer_addr = %peer.addr, "handshake timed out");
+            }
+        }
+    }
+
+    /// Serves a freshly accepted connection: the PROXY header and TLS (if
+    /// configured), the transport upgrade, the Bolt handshake and the
+    /// message loop.
+    pub(crate) async fn serve_stream<S>(
+        self: Arc<Self>,
+        mut stream: S,
+        mut peer: PeerInfo,
+        transport: Transport,
+    ) where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let Some(admission) = self.admit(&peer) else {
+            return;
+        };
+
+        if let Some(proxy) = &self.proxy_protocol
+            && proxy.is_trusted(peer.addr.ip())
+        {
+            let header = tokio::select! {
+                result = read_proxy_header(&mut stream) => result,
+                () = self.setup_abandoned(&admission, &peer) => return,
+            };
+            match header {
+                Ok(Some(client_addr)) => {
+                    tracing::debug!(proxy_addr = %peer.addr, %client_addr, "PROXY header received");
+                    peer.addr = client_addr;
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    tracing::debug!(peer_addr = %peer.addr, error = %e, "PROXY header rejected");
+                    return;
+                }
+            }
+        }
+
+        // On an auto-detecting port only connections that start with a TLS
+        // handshake are decrypted.
+        #[cfg(feature = "tls")]
+        if self.tls_acceptor.is_some() && !transport.is_auto() {
+            if let Some((tls_stream, peer)) = self.accept_tls(stream, peer, &admission).await {
+                self.serve_transport(tls_stream, peer, transport, admission)
+                    .await;
+            }
+            return;
+        }
+
+        self.serve_transport(stream, peer, transport, admission)
+            .await;
+    }
+
+    /// Performs the TLS handshake, recording the client certificate on the
+    /// peer. Returns `None` if it fails or setup is abandoned.
+    #[cfg(feature = "tls")]
+    async fn accept_tls<S>(
+        &self,
+        stream: S,
+        peer: PeerInfo,
+        admission: &Admission,
+    ) -> Option<(tokio_rustls::server::TlsStream<S>, PeerInfo)>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let acceptor = self.tls_acceptor.clone()?;
+        let accepted = tokio::select! {
+            result = acceptor.accept(stream) => result,
+            () = self.setup_abandoned(admission, &peer) => return None,
+        };
+        match accepted {
+            Ok(tls_stream) => {
+                let peer = PeerInfo {
+                    certificate: peer_certificate(&tls_stream),
+                    ..peer
+                };
+                Some((tls_stream, peer))
+            }
+            Err(e) => {
+                tracing::debug!(peer_addr = %peer.addr, error = %e, "TLS handshake failed");
+                None
+            }
+        }
+    }
+
+    async fn serve_transport<S>(
+        self: &Arc<Self>,
+        stream: S,
+        peer: PeerInfo,
+        transport: Transport,
+        admission: Admission,
+    ) where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        match transport {
+            Transport::Bolt => {
+                self.run_handshake_and_connection(stream, peer, admission)
+                    .await;
+            }
+            #[cfg(feature = "ws")]
+            Transport::WebSocket => self.serve_websocket(stream, peer, admission).await,
+            #[cfg(feature = "ws")]
+            Transport::Auto => {
+                self.serve_detected(Rewind::new(stream), peer, admission)
+                    .await;
+            }
+        }
+    }
+
+    /// Performs the WebSocket upgrade, then runs Bolt inside binary frames.
+    #[cfg(feature = "ws")]
+    async fn serve_websocket<S>(self: &Arc<Self>, stream: S, peer: PeerInfo, admission: Admission)
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let upgraded = tokio::select! {
+            result = tokio_tungstenite::accept_async(stream) => result,
+            () = self.setup_abandoned(&admission, &peer) => return,
+        };
+        match upgraded {
+            Ok(ws_stream) => {
+                let adapted = crate::ws::WsStream::new(ws_stream);
+                self.run_handshake_and_connection(adapted, peer, admission)
+                    .await;
+            }
+            Err(e) => {
+                tracing::debug!(peer_addr = %peer.addr, error = %e, "WebSocket upgrade failed");
+            }
+        }
+    }
+
+    /// Routes a connection by the protocol its first bytes announce. A TLS
+    /// connection is decrypted and detection runs again on the plaintext.
+    #[cfg(feature = "ws")]
+    async fn serve_detected<S>(
+        self: &Arc<Self>,
+        mut stream: Rewind<S>,
+        peer: PeerInfo,
+        admission: Admission,
+    ) where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let protocol = tokio::select! {
+            result = detect(&mut stream) => result,
+            () = self.setup_abandoned(&admission, &peer) => return,
+        };
+        #[cfg(feature = "tls")]
+        if let Ok(Protocol::Tls) = protocol
+            && self.tls_acceptor.is_some()
+        {
+            let Some((tls_stream, peer)) = self.accept_tls(stream, peer, &admission).await else {
+                return;
+            };
+            let mut stream = Rewind::new(tls_stream);
+            let protocol = tokio::select! {
+                result = detect(&mut stream) => result,
+                () = self.setup_abandoned(&admission, &peer) => return,
+            };
+            self.serve_protocol(stream, protocol, peer, admission).await;
+            return;
+        }
+        self.serve_protocol(stream, protocol, peer, admission).await;
+    }
+
+    /// Serves a connection whose protocol has been detected.
+    #[cfg(feature = "ws")]
+    async fn serve_protocol<S>(
+        self: &Arc<Self>,
+        mut stream: Rewind<S>,
+        protocol: std::io::Result<Protocol>,
+        peer: PeerInfo,
+        admission: Admission,
+    ) where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        match protocol {
+            Ok(Protocol::Bolt) => {
+                self.run_handshake_and_connection(stream, peer, admission)
+                    .await;
+            }
+            Ok(Protocol::WebSocket) => self.serve_websocket(stream, peer, admission).await,
+            Ok(Protocol::Http) => match &self.http_handler {
+                Some(handler) => handler.handle(stream.into_sniffed(), peer).await,
+                None => {
+                    let _ = stream.write_all(HTTP_NOT_FOUND).await;
+                    let _ = stream.shutdown().await;
+                }
+            },
+            Ok(Protocol::Tls) => {
+                tracing::debug!(peer_addr = %peer.addr, "TLS connection without TLS configured");
+            }
+            Ok(Protocol::Unknown) => {
+                tracing::debug!(peer_addr = %peer.addr, "unrecognized protocol");
+            }
+            Err(e) => {
+                tracing::debug!(peer_addr = %peer.addr, error = %e, "protocol detection failed");
+            }
+        }
+    }
+
+    /// Runs the Bolt handshake and then the message loop on `stream`.
+    pub(crate) async fn run_handshake_and_connection<S>(
+        self: &Arc<Self>,
+        stream: S,
+        peer: PeerInfo,
+        admission: Admission,
+    ) where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let mut stream = stream;
+        let peer_addr = peer.addr;
+        let handshake = tokio::select! {
+            result = server_handshake(&mut stream) => result,
+            () = self.setup_abandoned(&admission, &peer) => return,
+        };
+        match handshake {
+            Ok(version) => {
+                tracing::debug!(%peer_addr, ?version, "Bolt handshake complete");
+                let (rh, wh) = tokio::io::split(stream);
+                let mut conn =
+                    Connection::with_context(rh, wh, self.clone(), peer, admission.permit);
+                if let Err(e) = conn.run().await {
+                    tracing::debug!(%peer_addr, error = %e, "Bolt connection closed");
+                }
+            }
+            Err(e) => {
+                tracing::debug!(%peer_addr, error = %e, "Bolt handshake failed");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::io::AsyncReadExt;
+
+    use crate::error::BoltError;
+    use crate::message::ServerMessage;
+    use crate::server::test_support::{
+        MockBackend, TestClient, failure_code, hello, logon, pull, run,
+    };
+    use crate::server::{
+        AuthCredentials, AuthInfo, AuthLockout, AuthValidator, BoltServer, RateLimit, ServerHandle,
+    };
+
+    async fn bind(builder: BoltServer<MockBackend>) -> ServerHandle {
+        builder.bind("127.0.0.1:0".parse().unwrap()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn slow_clients_are_disconnected() {
+        let handle = bind(
+            BoltServer::builder(MockBackend::default())
+                .handshake_timeout(Duration::from_millis(200))
+                .hello_timeout(Duration::from_millis(200))
+                .logon_timeout(Duration::from_millis(200)),
+        )
+        .await;
+        let addr = handle.local_addr();
+
+        // Never sends the handshake.
+        let mut silent = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let mut buf = [0u8; 1];
+        assert_eq!(silent.read(&mut buf).await.unwrap(), 0);
+
+        // Handshakes but never sends HELLO.
+        let mut no_hello = TestClient::connect(addr).await.unwrap();
+        assert!(no_hello.recv().await.is_err());
+
+        // Sends HELLO but never LOGON.
+        let mut no_logon = TestClient::connect(addr).await.unwrap();
+        let reply = no_logon.request(hello()).await.unwrap();
+        assert!(matches!(reply, ServerMessage::Success { .. }));
+        assert!(no_logon.recv().await.is_err());
+
+        // Authenticated connections are not affected.
+        let mut client = TestClient::connect(addr).await.unwrap();
+        client.authenticate().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        let reply = client.request(run("RETURN 1")).await.unwrap();
+        assert!(matches!(reply, ServerMessage::Success { .. }));
+
+        handle.shutdown();
+        handle.wait().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn unauthenticated_connections_are_capped() {
+        let handle =
+            bind(BoltServer::builder(MockBackend::default()).max_unauthenticated_connections(1))
+                .await;
+        let addr = handle.local_addr();
+
+        let mut first = TestClient::connect(addr).await.unwrap();
+        let refused = tokio::net::TcpStream::connect(addr).await.unwrap();
+        assert!(TestClient::handshake(refused).await.is_err());
+
+        // LOGON frees the slot.
+        first.authenticate().await.unwrap();
+        let mut second = TestClient::connect(addr).await.unwrap();
+        second.authenticate().await.unwrap();
+
+        handle.shutdown();
+        handle.wait().await.unwrap();
+    }
+
+    struct RejectAll;
+
+    #[async_trait::async_trait]
+    impl AuthValidator for RejectAll {
+        async fn validate(&self, _credentials: &AuthCredentials) -> Result<AuthInfo, BoltError> {
+            Err(BoltError::Authentication("denied".into()))
+        }
+    }
+
+    #[tokio::test]
+    async fn failed_logon_closes_connection() {
+        let handle = bind(BoltServer::builder(MockBackend::default()).auth(RejectAll)).await;
+
+        let mut client = TestClient::connect(handle.local_addr()).await.unwrap();
+        client.request(hello()).await.unwrap();
+        let reply = client.request(logon("basic")).await.unwrap();
+        assert!(matches!(reply, ServerMessage::Failure { .. }));
+        assert!(client.recv().await.is_err());
+
+        handle.shutdown();
+        handle.wait().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn repeated_auth_failures_lock_out() {
+        let handle = bind(
+            BoltServer::builder(MockBackend::default())
+                .auth(RejectAll)
+                .auth_lockout(AuthLockout {
+                    max_failures: 1,
+                    base_delay: Duration::from_secs(60),
+                    max_delay: Duration::from_secs(60),
+                }),
+        )
+        .await;
+
+        let mut codes = Vec::new();
+        for _ in 0..3 {
+            let mut client = TestClient::connect(handle.local_addr()).await.unwrap();
+            client.request(hello()).await.unwrap();
+            let reply = client.request(logon("basic")).await.unwrap();
+            codes.push(failure_code(&reply).unwrap().to_string());
+        }
+        assert_eq!(
+            codes,
+            [
+                "Neo.ClientError.Security.Unauthorized",
+                "Neo.ClientError.Security.Unauthorized",
+                "Neo.ClientError.Security.AuthenticationRateLimit",
+            ]
+        );
+
+        handle.shutdown();
+        handle.wait().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn session_caps_and_request_rate_limit() {
+        let handle = bind(
+            BoltServer::builder(MockBackend::default())
+                .max_sessions_per_ip(1)
+                .rate_limit(RateLimit::per_second(0.001).burst(1)),
+        )
+        .await;
+        let addr = handle.local_addr();
+
+        let mut client = TestClient::connect(addr).await.unwrap();
+        client.authenticate().await.unwrap();
+
+        let mut second = TestClient::connect(addr).await.unwrap();
+        let reply = second.request(hello()).await.unwrap();
+        assert_eq!(
+            failure_code(&reply),
+            Some("Neo.TransientError.Request.ResourceExhaustion")
+        );
+
+        let reply = client.request(run("RETURN 1")).await.unwrap();
+        assert!(matches!(reply, ServerMessage::Success { .. }));
+        let mut reply = client.request(pull()).await.unwrap();
+        while matches!(reply, ServerMessage::Record { .. }) {
+            reply = client.recv().await.unwrap();
+        }
+        let reply = client.request(run("RETURN 1")).await.unwrap();
+        assert_eq!(
+            failure_code(&reply),
+            Some("Neo.TransientError.Request.ResourceExhaustion")
+        );
+
+        handle.shutdown();
+        handle.wait().await.unwrap();
+    }
+}
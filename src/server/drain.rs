@@ -0,0 +1,107 @@
+//! Graceful shutdown coordination between a server and its connections.
+
+use tokio::sync::watch;
+
+/// Lifecycle phase of a server, observed by every connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Phase {
+    /// Serving normally.
+    Running,
+    /// No longer accepting connections. In-flight work may finish; new
+    /// requests are refused with a transient failure.
+    Draining,
+    /// Grace period over (or nothing left to wait for): connections close.
+    Closed,
+}
+
+/// Shared drain state: the current phase plus the number of connections
+/// with work in flight (an executing request, an open transaction, or an
+/// unconsumed result).
+#[derive(Debug)]
+pub(crate) struct Drain {
+    phase: watch::Sender<Phase>,
+    busy: watch::Sender<usize>,
+}
+
+impl Default for Drain {
+    fn default() -> Self {
+        Self {
+            phase: watch::Sender::new(Phase::Running),
+            busy: watch::Sender::new(0),
+        }
+    }
+}
+
+impl Drain {
+    pub(crate) fn subscribe(&self) -> watch::Receiver<Phase> {
+        self.phase.subscribe()
+    }
+
+    /// Enters [`Phase::Draining`].
+    pub(crate) fn begin(&self) {
+        self.phase.send_if_modified(|phase| {
+            let running = *phase == Phase::Running;
+            if running {
+                *phase = Phase::Draining;
+            }
+            running
+        });
+    }
+
+    /// Enters [`Phase::Closed`].
+    pub(crate) fn close(&self) {
+        self.phase.send_replace(Phase::Closed);
+    }
+
+    /// Resolves once the server is [`Phase::Closed`].
+    pub(crate) async fn closed(&self) {
+        let mut phase = self.subscribe();
+        // The sender lives as long as `self`, so this cannot fail.
+        let _ = phase.wait_for(|p| *p == Phase::Closed).await;
+    }
+
+    /// Resolves once no connection has work in flight.
+    pub(crate) async fn idle(&self) {
+        let mut busy = self.busy.subscribe();
+        let _ = busy.wait_for(|n| *n == 0).await;
+    }
+
+    /// Records a connection's busy flag changing from `was` to `now`.
+    pub(crate) fn set_busy(&self, was: bool, now: bool) {
+        match (was, now) {
+            (false, true) => self.busy.send_modify(|n| *n += 1),
+            (true, false) => self.busy.send_modify(|n| *n -= 1),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn idle_waits_for_busy_connections() {
+        let drain = Drain::default();
+        drain.set_busy(false, true);
+        drain.set_busy(true, true);
+
+        let pending =
+            tokio::time::timeout(std::time::Duration::from_millis(20), drain.idle()).await;
+        assert!(pending.is_err());
+
+        drain.set_busy(true, false);
+        drain.idle().await;
+    }
+
+    #[test]
+    fn begin_does_not_reopen_closed() {
+        let drain = Drain::default();
+        let phase = drain.subscribe();
+        drain.begin();
+        assert_eq!(*phase.borrow(), Phase::Draining);
+        drain.close();
+        drain.begin();
+        assert_eq!(*phase.borrow(), Phase::Closed);
+    }
+}
@@ -0,0 +1,286 @@
+//! Handle to a running Bolt server: bound address, stats, shutdown.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+use crate::error::BoltError;
+use crate::server::session_manager::SessionManager;
+
+/// Live counters shared between a server and its connections.
+#[derive(Debug, Default)]
+pub(crate) struct ServerCounters {
+    total_connections: AtomicU64,
+    messages_processed: AtomicU64,
+}
+
+impl ServerCounters {
+    pub(crate) fn connection_accepted(&self) {
+        self.total_connections.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn message_processed(&self) {
+        self.messages_processed.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// A point-in-time snapshot of server activity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServerStats {
+    /// Sessions currently registered with the session manager.
+    pub active_sessions: usize,
+    /// Connections accepted since the server started.
+    pub total_connections: u64,
+    /// Bolt messages processed since the server started (NOOPs excluded).
+    pub messages_processed: u64,
+}
+
+/// Handle to a server started with [`BoltServer::bind`](crate::server::BoltServer::bind).
+///
+/// Dropping the handle does not stop the server; call [`shutdown`](Self::shutdown)
+/// and then [`wait`](Self::wait) to stop it.
+///
+/// ```rust,no_run
+/// use boltr::server::BoltServer;
+/// # use boltr::server::BoltBackend;
+///
+/// # async fn example(backend: impl BoltBackend) -> Result<(), boltr::error::BoltError> {
+/// let handle = BoltServer::builder(backend)
+///     .bind("127.0.0.1:0".parse().unwrap())
+///     .await?;
+/// println!("listening on {}", handle.local_addr());
+///
+/// handle.shutdown();
+/// handle.wait().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ServerHandle {
+    local_addr: SocketAddr,
+    shutdown: watch::Sender<bool>,
+    task: JoinHandle<Result<(), BoltError>>,
+    session_manager: Arc<SessionManager>,
+    counters: Arc<ServerCounters>,
+}
+
+impl ServerHandle {
+    pub(crate) fn new(
+        local_addr: SocketAddr,
+        shutdown: watch::Sender<bool>,
+        task: JoinHandle<Result<(), BoltError>>,
+        session_manager: Arc<SessionManager>,
+        counters: Arc<ServerCounters>,
+    ) -> Self {
+        Self {
+            local_addr,
+            shutdown,
+            task,
+            session_manager,
+            counters,
+        }
+    }
+
+    /// Returns the address the listener is bound to (useful after binding port 0).
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Signals the server to stop accepting connections. Idempotent.
+    pub fn shutdown(&self) {
+        self.shutdown.send_replace(true);
+    }
+
+    /// Waits for the server to stop.
+    pub async fn wait(self) -> Result<(), BoltError> {
+        match self.task.await {
+            Ok(result) => result,
+            Err(e) => Err(BoltError::Io(std::io::Error::other(e))),
+        }
+    }
+
+    /// Returns a snapshot of the server's live counters.
+    pub fn stats(&self) -> ServerStats {
+        ServerStats {
+            active_sessions: self.session_manager.count(),
+            total_connections: self.counters.total_connections.load(Ordering::Relaxed),
+            messages_processed: self.counters.messages_processed.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::message::{ClientMessage, ServerMessage};
+    use crate::server::BoltServer;
+    use crate::server::test_support::{MockBackend, TestClient, failure_code, pull, run};
+    use crate::types::BoltDict;
+
+    #[tokio::test]
+    async fn bind_reports_address_and_stats() {
+        let handle = BoltServer::builder(MockBackend::default())
+            .bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        assert_ne!(handle.local_addr().port(), 0);
+
+        let mut client = TestClient::connect(handle.local_addr()).await.unwrap();
+        client.authenticate().await.unwrap();
+        let reply = client.request(run("RETURN 1")).await.unwrap();
+        assert!(matches!(reply, ServerMessage::Success { .. }));
+
+        let stats = handle.stats();
+        assert_eq!(stats.active_sessions, 1);
+        assert_eq!(stats.total_connections, 1);
+        assert_eq!(stats.messages_processed, 3);
+
+        handle.shutdown();
+        handle.wait().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn shutdown_drains_in_flight_work() {
+        let backend = MockBackend::default();
+        let handle = BoltServer::builder(backend.clone())
+            .shutdown_grace_period(Duration::from_secs(5))
+            .bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let addr = handle.local_addr();
+        let begin = || ClientMessage::Begin {
+            extra: BoltDict::new(),
+        };
+
+        let mut running = TestClient::connect(addr).await.unwrap();
+        running.authenticate().await.unwrap();
+        let mut idle_tx = TestClient::connect(addr).await.unwrap();
+        idle_tx.authenticate().await.unwrap();
+        assert!(matches!(
+            idle_tx.request(begin()).await.unwrap(),
+            ServerMessage::Success { .. }
+        ));
+        let mut running_tx = TestClient::connect(addr).await.unwrap();
+        running_tx.authenticate().await.unwrap();
+        assert!(matches!(
+            running_tx.request(begin()).await.unwrap(),
+            ServerMessage::Success { .. }
+        ));
+        let mut idle = TestClient::connect(addr).await.unwrap();
+        idle.authenticate().await.unwrap();
+
+        running.send(run("SLEEP 200")).await.unwrap();
+        running_tx.send(run("SLEEP 200")).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let started = Instant::now();
+        handle.shutdown();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // New work is refused with a retryable error.
+        let unavailable = Some("Neo.TransientError.General.DatabaseUnavailable");
+        let refused = idle.request(run("RETURN 1")).await.unwrap();
+        assert_eq!(failure_code(&refused), unavailable);
+
+        // A transaction idle when draining began is rolled back; its next
+        // request fails.
+        assert_eq!(*backend.rolled_back.lock().unwrap(), vec!["s1-tx"]);
+        let refused = idle_tx.request(run("RETURN 1")).await.unwrap();
+        assert_eq!(failure_code(&refused), unavailable);
+        assert!(matches!(
+            idle_tx.request(ClientMessage::Reset).await.unwrap(),
+            ServerMessage::Success { .. }
+        ));
+
+        // A running transaction can finish its query, run more and commit.
+        assert!(matches!(
+            running_tx.recv().await.unwrap(),
+            ServerMessage::Success { .. }
+        ));
+        assert!(matches!(
+            running_tx.request(pull()).await.unwrap(),
+            ServerMessage::Record { .. }
+        ));
+        assert!(matches!(
+            running_tx.recv().await.unwrap(),
+            ServerMessage::Success { .. }
+        ));
+        assert!(matches!(
+            running_tx.request(run("RETURN 1")).await.unwrap(),
+            ServerMessage::Success { .. }
+        ));
+        assert!(matches!(
+            running_tx.request(pull()).await.unwrap(),
+            ServerMessage::Record { .. }
+        ));
+        assert!(matches!(
+            running_tx.recv().await.unwrap(),
+            ServerMessage::Success { .. }
+        ));
+        assert!(matches!(
+            running_tx.request(ClientMessage::Commit).await.unwrap(),
+            ServerMessage::Success { .. }
+        ));
+
+        // The running query completes and its records can still be pulled.
+        assert!(matches!(
+            running.recv().await.unwrap(),
+            ServerMessage::Success { .. }
+        ));
+        assert!(matches!(
+            running.request(pull()).await.unwrap(),
+            ServerMessage::Record { .. }
+        ));
+        assert!(matches!(
+            running.recv().await.unwrap(),
+            ServerMessage::Success { .. }
+        ));
+
+        // Nothing waits for the grace period.
+        handle.wait().await.unwrap();
+        assert!(started.elapsed() < Duration::from_secs(2));
+        let mut closed = backend.closed.lock().unwrap().clone();
+        closed.sort();
+        assert_eq!(closed, vec!["s0", "s1", "s2", "s3"]);
+        assert_eq!(backend.rolled_back.lock().unwrap().len(), 1);
+        assert!(running.recv().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn shutdown_closes_connections_after_grace_period() {
+        let backend = MockBackend::default();
+        let handle = BoltServer::builder(backend.clone())
+            .shutdown_grace_period(Duration::from_millis(100))
+            .bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+
+        let mut client = TestClient::connect(handle.local_addr()).await.unwrap();
+        client.authenticate().await.unwrap();
+        client.send(run("SLEEP 10000")).await.unwrap();
+        let mut in_tx = TestClient::connect(handle.local_addr()).await.unwrap();
+        in_tx.authenticate().await.unwrap();
+        let begin = ClientMessage::Begin {
+            extra: BoltDict::new(),
+        };
+        assert!(matches!(
+            in_tx.request(begin).await.unwrap(),
+            ServerMessage::Success { .. }
+        ));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let started = Instant::now();
+        handle.shutdown();
+        handle.wait().await.unwrap();
+        assert!(started.elapsed() < Duration::from_secs(5));
+        let mut closed = backend.closed.lock().unwrap().clone();
+        closed.sort();
+        assert_eq!(closed, vec!["s0", "s1"]);
+        // The idle transaction was rolled back when draining began.
+        assert_eq!(*backend.rolled_back.lock().unwrap(), vec!["s1-tx"]);
+        assert!(client.recv().await.is_err());
+        assert!(in_tx.recv().await.is_err());
+    }
+}
@@ -0,0 +1,286 @@
+//! Request rate limiting and authentication failure lockout.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::error::BoltError;
+
+/// Token-bucket limit on RUN and BEGIN requests, applied per authenticated
+/// principal (or per remote IP when no principal is known).
+///
+/// ```rust
+/// use boltr::server::RateLimit;
+///
+/// // 50 requests per second on average, bursts of up to 200.
+/// let limit = RateLimit::per_second(50.0).burst(200);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimit {
+    rate: f64,
+    burst: f64,
+}
+
+impl RateLimit {
+    /// Allows `rate` requests per second, with bursts of the same size.
+    pub fn per_second(rate: f64) -> Self {
+        Self {
+            rate,
+            burst: rate.max(1.0),
+        }
+    }
+
+    /// Sets the bucket size: how many requests may be made at once after
+    /// a quiet period.
+    pub fn burst(mut self, burst: u32) -> Self {
+        self.burst = f64::from(burst.max(1));
+        self
+    }
+}
+
+/// Exponential back-off after repeated LOGON failures, tracked separately
+/// per principal and per remote IP.
+///
+/// After `max_failures` failures the key is locked for `base_delay`; every
+/// further failure doubles the lockout, up to `max_delay`. A successful
+/// LOGON clears the principal's count; an IP's count is forgotten once it
+/// has seen no failure for `max_delay`. While locked, LOGON
+/// fails with `Neo.ClientError.Security.AuthenticationRateLimit` without
+/// consulting the [`AuthValidator`](crate::server::AuthValidator).
+#[derive(Debug, Clone, Copy)]
+pub struct AuthLockout {
+    /// Failures allowed before locking.
+    pub max_failures: u32,
+    /// Lockout after the first failure beyond `max_failures`.
+    pub base_delay: Duration,
+    /// Upper bound on a single lockout.
+    pub max_delay: Duration,
+}
+
+impl Default for AuthLockout {
+    /// Five attempts, then one second doubling up to fifteen minutes.
+    fn default() -> Self {
+        Self {
+            max_failures: 5,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(15 * 60),
+        }
+    }
+}
+
+/// Number of tracked keys above which stale entries are pruned.
+const PRUNE_THRESHOLD: usize = 4096;
+
+/// Minimum time between two prunes, so that a large map of partly used
+/// buckets is not scanned on every request.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A principal or remote IP, tracked separately so that a principal named
+/// like an address does not share its budget.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Key {
+    Principal(String),
+    Ip(IpAddr),
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+#[derive(Debug)]
+struct Buckets {
+    by_key: HashMap<Key, Bucket>,
+    last_prune: Instant,
+}
+
+/// Per-key token buckets for a [`RateLimit`].
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    limit: RateLimit,
+    buckets: Mutex<Buckets>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            buckets: Mutex::new(Buckets {
+                by_key: HashMap::new(),
+                last_prune: Instant::now(),
+            }),
+        }
+    }
+
+    /// Takes one token from the principal's bucket, or from the IP's when
+    /// no principal is known.
+    pub(crate) fn acquire(&self, principal: Option<&str>, ip: IpAddr) -> Result<(), BoltError> {
+        let now = Instant::now();
+        let RateLimit { rate, burst } = self.limit;
+        let mut buckets = self.buckets.lock().unwrap();
+        let Buckets { by_key, last_prune } = &mut *buckets;
+
+        if by_key.len() >= PRUNE_THRESHOLD && now.duration_since(*last_prune) >= PRUNE_INTERVAL {
+            // Buckets that have refilled are indistinguishable from new ones.
+            by_key.retain(|_, b| {
+                b.tokens + now.duration_since(b.updated).as_secs_f64() * rate < burst
+            });
+            *last_prune = now;
+        }
+
+        let key = match principal {
+            Some(principal) => Key::Principal(principal.to_string()),
+            None => Key::Ip(ip.to_canonical()),
+        };
+        let bucket = by_key.entry(key).or_insert(Bucket {
+            tokens: burst,
+            updated: now,
+        });
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(burst);
+        bucket.updated = now;
+
+        if bucket.tokens < 1.0 {
+            return Err(BoltError::RateLimited(format!(
+                "request rate limit of {rate}/s exceeded"
+            )));
+        }
+        bucket.tokens -= 1.0;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct Failures {
+    count: u32,
+    last_failure: Instant,
+    locked_until: Option<Instant>,
+}
+
+/// LOGON failure counts and lockouts for an [`AuthLockout`].
+#[derive(Debug)]
+pub(crate) struct LockoutTracker {
+    policy: AuthLockout,
+    failures: Mutex<HashMap<Key, Failures>>,
+}
+
+impl LockoutTracker {
+    pub(crate) fn new(policy: AuthLockout) -> Self {
+        Self {
+            policy,
+            failures: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn keys(principal: Option<&str>, ip: IpAddr) -> impl Iterator<Item = Key> {
+        principal
+            .map(|p| Key::Principal(p.to_string()))
+            .into_iter()
+            .chain(std::iter::once(Key::Ip(ip.to_canonical())))
+    }
+
+    /// Fails if either the principal or the IP is currently locked out.
+    pub(crate) fn check(&self, principal: Option<&str>, ip: IpAddr) -> Result<(), BoltError> {
+        let now = Instant::now();
+        let failures = self.failures.lock().unwrap();
+        for key in Self::keys(principal, ip) {
+            if let Some(until) = failures.get(&key).and_then(|f| f.locked_until)
+                && until > now
+            {
+                let secs = until.duration_since(now).as_secs().max(1);
+                return Err(BoltError::AuthenticationRateLimit(format!(
+                    "too many failed authentication attempts, retry in {secs}s"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn record_failure(&self, principal: Option<&str>, ip: IpAddr) {
+        let now = Instant::now();
+        let AuthLockout {
+            max_failures,
+            base_delay,
+            max_delay,
+        } = self.policy;
+        let mut failures = self.failures.lock().unwrap();
+
+        failures.retain(|_, f| now.duration_since(f.last_failure) <= max_delay);
+
+        for key in Self::keys(principal, ip) {
+            let entry = failures.entry(key).or_insert(Failures {
+                count: 0,
+                last_failure: now,
+                locked_until: None,
+            });
+            entry.count = entry.count.saturating_add(1);
+            entry.last_failure = now;
+            if entry.count > max_failures {
+                let doublings = (entry.count - max_failures - 1).min(31);
+                let delay = base_delay.saturating_mul(1 << doublings).min(max_delay);
+                entry.locked_until = Some(now + delay);
+            }
+        }
+    }
+
+    /// Clears the principal's failures. The IP's are kept, so one valid
+    /// account does not reset a credential-stuffing source.
+    pub(crate) fn record_success(&self, principal: &str) {
+        self.failures
+            .lock()
+            .unwrap()
+            .remove(&Key::Principal(principal.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_bucket_allows_burst_then_limits() {
+        let limiter = RateLimiter::new(RateLimit::per_second(0.001).burst(3));
+        let ip: IpAddr = "10.0.0.1".parse().unwrap();
+        for _ in 0..3 {
+            limiter.acquire(Some("alice"), ip).unwrap();
+        }
+        assert!(matches!(
+            limiter.acquire(Some("alice"), ip),
+            Err(BoltError::RateLimited(_))
+        ));
+        // Buckets are independent per key, and principals named like an
+        // address do not share its bucket.
+        limiter.acquire(Some("bob"), ip).unwrap();
+        for _ in 0..3 {
+            limiter.acquire(Some("10.0.0.1"), ip).unwrap();
+        }
+        limiter.acquire(None, ip).unwrap();
+    }
+
+    #[test]
+    fn lockout_after_repeated_failures() {
+        let tracker = LockoutTracker::new(AuthLockout {
+            max_failures: 2,
+            base_delay: Duration::from_secs(60),
+            max_delay: Duration::from_secs(600),
+        });
+        let ip: IpAddr = "192.0.2.1".parse().unwrap();
+        let other_ip: IpAddr = "192.0.2.2".parse().unwrap();
+
+        tracker.record_failure(Some("alice"), ip);
+        tracker.record_failure(Some("alice"), ip);
+        tracker.check(Some("alice"), ip).unwrap();
+
+        tracker.record_failure(Some("alice"), ip);
+        // Locked by principal from any IP, and by IP for any principal.
+        assert!(tracker.check(Some("alice"), other_ip).is_err());
+        assert!(tracker.check(Some("bob"), ip).is_err());
+        tracker.check(Some("bob"), other_ip).unwrap();
+
+        tracker.record_success("alice");
+        tracker.check(Some("alice"), other_ip).unwrap();
+        assert!(tracker.check(Some("alice"), ip).is_err());
+    }
+}
@@ -0,0 +1,53 @@
+//! Listener abstraction for serving Bolt on sockets other than a bound TCP port.
+
+#[cfg(unix)]
+use std::net::{Ipv4Addr, SocketAddr};
+
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::server::peer::PeerInfo;
+
+/// A source of incoming byte streams for [`BoltServer::serve_listener`].
+///
+/// Implemented for [`tokio::net::TcpListener`] (including listeners built
+/// from a systemd socket-activated fd via `TcpListener::from_std`) and, on
+/// Unix, [`tokio::net::UnixListener`]. Implement it for your own type to
+/// feed connections from a protocol multiplexer or any other source.
+///
+/// [`BoltServer::serve_listener`]: crate::server::BoltServer::serve_listener
+#[async_trait::async_trait]
+pub trait BoltListener: Send + 'static {
+    /// The stream type produced for each connection.
+    type Stream: AsyncRead + AsyncWrite + Unpin + Send + 'static;
+
+    /// Waits for the next connection.
+    ///
+    /// Errors are logged and the accept loop continues.
+    async fn accept(&mut self) -> std::io::Result<(Self::Stream, PeerInfo)>;
+}
+
+#[async_trait::async_trait]
+impl BoltListener for tokio::net::TcpListener {
+    type Stream = tokio::net::TcpStream;
+
+    async fn accept(&mut self) -> std::io::Result<(Self::Stream, PeerInfo)> {
+        let (stream, addr) = tokio::net::TcpListener::accept(self).await?;
+        Ok((stream, PeerInfo::new(addr)))
+    }
+}
+
+/// Unix domain socket peers have no IP address; they are reported as
+/// `0.0.0.0:0`.
+#[cfg(unix)]
+#[async_trait::async_trait]
+impl BoltListener for tokio::net::UnixListener {
+    type Stream = tokio::net::UnixStream;
+
+    async fn accept(&mut self) -> std::io::Result<(Self::Stream, PeerInfo)> {
+        let (stream, _) = tokio::net::UnixListener::accept(self).await?;
+        Ok((
+            stream,
+            PeerInfo::new(SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0))),
+        ))
+    }
+}
@@ -2,20 +2,44 @@
 
 pub mod auth;
 pub mod backend;
+pub mod bookmarks;
 pub mod builder;
 pub mod connection;
+pub(crate) mod context;
+pub(crate) mod drain;
+pub mod handle;
 pub mod handshake;
+pub mod limits;
+pub mod listener;
+pub mod peer;
+pub mod proxy;
+pub mod router;
+pub mod service;
 pub mod session_manager;
+#[cfg(feature = "ws")]
+pub mod sniff;
 pub mod state_machine;
+#[cfg(test)]
+pub(crate) mod test_support;
+#[cfg(feature = "tls")]
+pub mod tls;
 
-pub use auth::{AuthInfo, AuthValidator};
+pub use auth::{AuthInfo, AuthValidator, AuthorizationRequest, Authorizer, Operation};
 pub use backend::{
     AccessMode, AuthCredentials, BoltBackend, BoltRecord, ResultMetadata, ResultStream,
     RoutingServer, RoutingTable, SessionConfig, SessionHandle, SessionProperty, TransactionHandle,
     extract_bookmarks,
 };
+pub use bookmarks::BookmarkManager;
 pub use builder::BoltServer;
-#[cfg(feature = "tls")]
-pub use builder::TlsConfig;
+pub use handle::{ServerHandle, ServerStats};
+pub use limits::{AuthLockout, RateLimit};
+pub use listener::BoltListener;
+pub use peer::{PeerCertificate, PeerInfo};
+pub use proxy::ProxyProtocol;
+pub use router::DatabaseRouter;
+pub use service::BoltService;
 pub use session_manager::SessionManager;
 pub use state_machine::ConnectionState;
+#[cfg(feature = "tls")]
+pub use tls::{CertificateResolver, ClientAuth, TlsConfig};
@@ -0,0 +1,135 @@
+//! Identity of the remote end of a Bolt connection.
+
+use std::net::SocketAddr;
+
+#[cfg(feature = "tls")]
+use crate::error::BoltError;
+
+/// A verified client certificate presented during a mutual TLS handshake.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerCertificate {
+    /// Subject distinguished name, e.g. `CN=svc-a,O=Example`.
+    pub subject: String,
+    /// Subject alternative names (DNS names, IP addresses, URIs, emails).
+    pub subject_alt_names: Vec<String>,
+    /// Raw DER encoding of the end-entity certificate.
+    pub der: Vec<u8>,
+}
+
+impl PeerCertificate {
+    /// Parses the subject and subject alternative names from a DER certificate.
+    #[cfg(feature = "tls")]
+    pub fn from_der(der: &[u8]) -> Result<Self, BoltError> {
+        use x509_parser::extensions::GeneralName;
+
+        let (_, cert) = x509_parser::parse_x509_certificate(der)
+            .map_err(|e| BoltError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, e)))?;
+
+        let subject_alt_names = match cert.subject_alternative_name() {
+            Ok(Some(ext)) => ext
+                .value
+                .general_names
+                .iter()
+                .filter_map(|name| match name {
+                    GeneralName::DNSName(s) | GeneralName::RFC822Name(s) | GeneralName::URI(s) => {
+                        Some((*s).to_string())
+                    }
+                    GeneralName::IPAddress(bytes) => ip_from_bytes(bytes),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        Ok(Self {
+            subject: cert.subject().to_string(),
+            subject_alt_names,
+            der: der.to_vec(),
+        })
+    }
+
+    /// Returns the first common name (`CN`) of the subject, if any.
+    #[must_use]
+    pub fn common_name(&self) -> Option<&str> {
+        self.subject
+            .split(',')
+            .map(str::trim)
+            .find_map(|rdn| rdn.strip_prefix("CN="))
+    }
+}
+
+#[cfg(feature = "tls")]
+fn ip_from_bytes(bytes: &[u8]) -> Option<String> {
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    match bytes.len() {
+        4 => <[u8; 4]>::try_from(bytes)
+            .ok()
+            .map(|b| Ipv4Addr::from(b).to_string()),
+        16 => <[u8; 16]>::try_from(bytes)
+            .ok()
+            .map(|b| Ipv6Addr::from(b).to_string()),
+        _ => None,
+    }
+}
+
+/// Information about the remote peer of a connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerInfo {
+    /// Remote socket address.
+    pub addr: SocketAddr,
+    /// Client certificate verified during the TLS handshake, if any.
+    pub certificate: Option<PeerCertificate>,
+}
+
+impl PeerInfo {
+    /// Creates peer info for a plain (non-mTLS) connection.
+    pub fn new(addr: SocketAddr) -> Self {
+        Self {
+            addr,
+            certificate: None,
+        }
+    }
+}
+
+impl From<SocketAddr> for PeerInfo {
+    fn from(addr: SocketAddr) -> Self {
+        Self::new(addr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn common_name_from_subject() {
+        let cert = PeerCertificate {
+            subject: "O=Example, CN=svc-a".into(),
+            subject_alt_names: vec![],
+            der: vec![],
+        };
+        assert_eq!(cert.common_name(), Some("svc-a"));
+    }
+
+    #[cfg(feature = "tls")]
+    #[test]
+    fn parse_subject_and_sans() {
+        let mut params = rcgen::CertificateParams::new(vec!["svc-a.internal".to_string()]).unwrap();
+        params
+            .distinguished_name
+            .push(rcgen::DnType::CommonName, "svc-a");
+        params
+            .subject_alt_names
+            .push(rcgen::SanType::IpAddress("10.0.0.7".parse().unwrap()));
+        let key = rcgen::KeyPair::generate().unwrap();
+        let cert = params.self_signed(&key).unwrap();
+
+        let parsed = PeerCertificate::from_der(cert.der()).unwrap();
+        assert_eq!(parsed.common_name(), Some("svc-a"));
+        assert_eq!(
+            parsed.subject_alt_names,
+            vec!["svc-a.internal".to_string(), "10.0.0.7".to_string()]
+        );
+    }
+}
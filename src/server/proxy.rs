@@ -0,0 +1,316 @@
+//! HAProxy PROXY protocol (v1 and v2) for servers behind L4 load balancers.
+//!
+//! A trusted proxy prepends a header carrying the original client address
+//! before any other byte of the connection. The header is consumed before
+//! TLS and the Bolt handshake, and the client address replaces the socket
+//! address in [`PeerInfo`](crate::server::PeerInfo), so it shows up in the
+//! session manager, authentication and logs.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::error::BoltError;
+
+/// Signature opening every v2 header.
+const V2_SIGNATURE: [u8; 12] = *b"\r\n\r\n\0\r\nQUIT\n";
+
+/// Maximum length of a v1 header line, including the trailing CRLF.
+const V1_MAX_LEN: usize = 107;
+
+/// PROXY protocol configuration: which connection sources are trusted to
+/// send a header.
+///
+/// Connections from trusted sources must start with a v1 or v2 header and
+/// are closed otherwise. Connections from any other source are served
+/// as-is, with their socket address.
+///
+/// ```rust
+/// use boltr::server::ProxyProtocol;
+///
+/// let proxy = ProxyProtocol::new()
+///     .trust("192.0.2.10".parse().unwrap())
+///     .trust_network("10.0.0.0".parse().unwrap(), 8);
+/// assert!(proxy.is_trusted("10.1.2.3".parse().unwrap()));
+/// assert!(!proxy.is_trusted("192.0.2.11".parse().unwrap()));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ProxyProtocol {
+    trusted: Vec<(IpAddr, u8)>,
+}
+
+impl ProxyProtocol {
+    /// Creates a configuration that trusts no source yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Trusts a single proxy address.
+    pub fn trust(self, addr: IpAddr) -> Self {
+        let prefix_len = if addr.is_ipv4() { 32 } else { 128 };
+        self.trust_network(addr, prefix_len)
+    }
+
+    /// Trusts every address in `network/prefix_len` (CIDR notation).
+    pub fn trust_network(mut self, network: IpAddr, prefix_len: u8) -> Self {
+        self.trusted.push((network.to_canonical(), prefix_len));
+        self
+    }
+
+    /// Returns whether connections from `addr` must carry a PROXY header.
+    pub fn is_trusted(&self, addr: IpAddr) -> bool {
+        let addr = addr.to_canonical();
+        self.trusted
+            .iter()
+            .any(|&(network, prefix_len)| in_network(addr, network, prefix_len))
+    }
+}
+
+fn in_network(addr: IpAddr, network: IpAddr, prefix_len: u8) -> bool {
+    match (addr, network) {
+        (IpAddr::V4(a), IpAddr::V4(n)) => {
+            let shift = 32 - u32::from(prefix_len.min(32));
+            let mask = u32::MAX.checked_shl(shift).unwrap_or(0);
+            u32::from(a) & mask == u32::from(n) & mask
+        }
+        (IpAddr::V6(a), IpAddr::V6(n)) => {
+            let shift = 128 - u32::from(prefix_len.min(128));
+            let mask = u128::MAX.checked_shl(shift).unwrap_or(0);
+            u128::from(a) & mask == u128::from(n) & mask
+        }
+        _ => false,
+    }
+}
+
+/// Reads a v1 or v2 header from the start of `stream`, consuming exactly
+/// the header bytes.
+///
+/// Returns the original client address, or `None` when the proxy sent no
+/// address (v1 `UNKNOWN`, v2 `LOCAL` or a non-IP address family).
+pub(crate) async fn read_header<S>(stream: &mut S) -> Result<Option<SocketAddr>, BoltError>
+where
+    S: AsyncRead + Unpin,
+{
+    let mut start = [0u8; 12];
+    stream.read_exact(&mut start).await?;
+
+    if start == V2_SIGNATURE {
+        let mut fixed = [0u8; 4];
+        stream.read_exact(&mut fixed).await?;
+        let len = u16::from_be_bytes([fixed[2], fixed[3]]) as usize;
+        let mut body = vec![0u8; len];
+        stream.read_exact(&mut body).await?;
+        return parse_v2(fixed[0], fixed[1], &body);
+    }
+
+    if start.starts_with(b"PROXY ") {
+        // The line is short and read once per connection, so byte-wise reads
+        // keep us from consuming anything past the CRLF.
+        let mut line = start.to_vec();
+        while !line.ends_with(b"\r\n") {
+            if line.len() >= V1_MAX_LEN {
+                return Err(invalid("v1 header too long"));
+            }
+            line.push(stream.read_u8().await?);
+        }
+        return parse_v1(&line);
+    }
+
+    Err(invalid("missing header"))
+}
+
+/// Parses a complete v1 line, e.g. `PROXY TCP4 203.0.113.7 192.0.2.1 51234 7687\r\n`.
+fn parse_v1(line: &[u8]) -> Result<Option<SocketAddr>, BoltError> {
+    let line = std::str::from_utf8(line)
+        .ok()
+        .and_then(|l| l.strip_suffix("\r\n"))
+        .ok_or_else(|| invalid("malformed v1 header"))?;
+    let parts: Vec<&str> = line.split(' ').collect();
+    match parts.as_slice() {
+        ["PROXY", "UNKNOWN", ..] => Ok(None),
+        [
+            "PROXY",
+            family @ ("TCP4" | "TCP6"),
+            src,
+            _dst,
+            src_port,
+            _dst_port,
+        ] => {
+            let ip: IpAddr = src.parse().map_err(|_| invalid("bad v1 source address"))?;
+            let port: u16 = src_port
+                .parse()
+                .map_err(|_| invalid("bad v1 source port"))?;
+            if ip.is_ipv4() != (*family == "TCP4") {
+                return Err(invalid("v1 address does not match protocol family"));
+            }
+            Ok(Some(SocketAddr::new(ip, port)))
+        }
+        _ => Err(invalid("malformed v1 header")),
+    }
+}
+
+/// Parses a v2 header from its version/command byte, family byte and body.
+fn parse_v2(ver_cmd: u8, family: u8, body: &[u8]) -> Result<Option<SocketAddr>, BoltError> {
+    if ver_cmd >> 4 != 2 {
+        return Err(invalid("unsupported v2 version"));
+    }
+    match ver_cmd & 0x0F {
+        // LOCAL: health check from the proxy itself.
+        0x0 => return Ok(None),
+        0x1 => {}
+        _ => return Err(invalid("unsupported v2 command")),
+    }
+    match family >> 4 {
+        // AF_INET: src addr, dst addr, src port, dst port.
+        0x1 => {
+            let b = body
+                .get(..12)
+                .ok_or_else(|| invalid("truncated v2 address"))?;
+            let ip = Ipv4Addr::new(b[0], b[1], b[2], b[3]);
+            let port = u16::from_be_bytes([b[8], b[9]]);
+            Ok(Some(SocketAddr::new(ip.into(), port)))
+        }
+        // AF_INET6.
+        0x2 => {
+            let b = body
+                .get(..36)
+                .ok_or_else(|| invalid("truncated v2 address"))?;
+            let mut octets = [0u8; 16];
+            octets.copy_from_slice(&b[..16]);
+            let port = u16::from_be_bytes([b[32], b[33]]);
+            Ok(Some(SocketAddr::new(Ipv6Addr::from(octets).into(), port)))
+        }
+        // AF_UNSPEC or AF_UNIX: no IP address to report.
+        _ => Ok(None),
+    }
+}
+
+fn invalid(reason: &str) -> BoltError {
+    BoltError::Protocol(format!("PROXY protocol: {reason}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn read(bytes: &[u8]) -> Result<Option<SocketAddr>, BoltError> {
+        let mut cursor = std::io::Cursor::new(bytes.to_vec());
+        let addr = read_header(&mut cursor).await?;
+        // Nothing past the header may be consumed.
+        let mut rest = Vec::new();
+        cursor.read_to_end(&mut rest).await.unwrap();
+        assert_eq!(rest, b"rest");
+        Ok(addr)
+    }
+
+    #[tokio::test]
+    async fn v1_header() {
+        let addr = read(b"PROXY TCP4 203.0.113.7 192.0.2.1 51234 7687\r\nrest")
+            .await
+            .unwrap();
+        assert_eq!(addr, Some("203.0.113.7:51234".parse().unwrap()));
+
+        let addr = read(b"PROXY TCP6 2001:db8::1 2001:db8::2 4000 7687\r\nrest")
+            .await
+            .unwrap();
+        assert_eq!(addr, Some("[2001:db8::1]:4000".parse().unwrap()));
+
+        assert_eq!(read(b"PROXY UNKNOWN\r\nrest").await.unwrap(), None);
+        assert!(
+            read(b"PROXY TCP4 2001:db8::1 192.0.2.1 1 2\r\nrest")
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn v2_header() {
+        let mut header = V2_SIGNATURE.to_vec();
+        header.extend_from_slice(&[0x21, 0x11, 0x00, 0x0C]);
+        header.extend_from_slice(&[203, 0, 113, 7, 192, 0, 2, 1]);
+        header.extend_from_slice(&51234u16.to_be_bytes());
+        header.extend_from_slice(&7687u16.to_be_bytes());
+        header.extend_from_slice(b"rest");
+        let addr = read(&header).await.unwrap();
+        assert_eq!(addr, Some("203.0.113.7:51234".parse().unwrap()));
+
+        let mut local = V2_SIGNATURE.to_vec();
+        local.extend_from_slice(&[0x20, 0x00, 0x00, 0x00]);
+        local.extend_from_slice(b"rest");
+        assert_eq!(read(&local).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn missing_header_is_rejected() {
+        let mut bolt = std::io::Cursor::new(vec![0x60, 0x60, 0xB0, 0x17, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert!(read_header(&mut bolt).await.is_err());
+    }
+
+    #[test]
+    fn trusted_networks() {
+        let proxy = ProxyProtocol::new()
+            .trust("192.0.2.10".parse().unwrap())
+            .trust_network("2001:db8::".parse().unwrap(), 32);
+        assert!(proxy.is_trusted("192.0.2.10".parse().unwrap()));
+        assert!(proxy.is_trusted("::ffff:192.0.2.10".parse().unwrap()));
+        assert!(!proxy.is_trusted("192.0.2.11".parse().unwrap()));
+        assert!(proxy.is_trusted("2001:db8:1::5".parse().unwrap()));
+        assert!(!proxy.is_trusted("2001:db9::5".parse().unwrap()));
+        assert!(
+            ProxyProtocol::new()
+                .trust_network("0.0.0.0".parse().unwrap(), 0)
+                .is_trusted("8.8.8.8".parse().unwrap())
+        );
+    }
+
+    struct RecordPeer(std::sync::Arc<std::sync::Mutex<Vec<SocketAddr>>>);
+
+    #[async_trait::async_trait]
+    impl crate::server::AuthValidator for RecordPeer {
+        async fn validate(
+            &self,
+            credentials: &crate::server::AuthCredentials,
+        ) -> Result<crate::server::AuthInfo, BoltError> {
+            self.0.lock().unwrap().push(credentials.peer.addr);
+            Ok(crate::server::AuthInfo::default())
+        }
+    }
+
+    #[tokio::test]
+    async fn client_address_reaches_auth() {
+        use crate::server::BoltServer;
+        use crate::server::test_support::{MockBackend, TestClient};
+        use tokio::io::AsyncWriteExt;
+
+        let peers = std::sync::Arc::default();
+        let handle = BoltServer::builder(MockBackend::default())
+            .auth(RecordPeer(std::sync::Arc::clone(&peers)))
+            .proxy_protocol(ProxyProtocol::new().trust("127.0.0.1".parse().unwrap()))
+            .bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+
+        let mut stream = tokio::net::TcpStream::connect(handle.local_addr())
+            .await
+            .unwrap();
+        stream
+            .write_all(b"PROXY TCP4 203.0.113.7 127.0.0.1 51234 7687\r\n")
+            .await
+            .unwrap();
+        let mut client = TestClient::handshake(stream).await.unwrap();
+        client.authenticate().await.unwrap();
+        assert_eq!(
+            *peers.lock().unwrap(),
+            vec!["203.0.113.7:51234".parse::<SocketAddr>().unwrap()]
+        );
+
+        // A trusted source without a header is turned away.
+        let stream = tokio::net::TcpStream::connect(handle.local_addr())
+            .await
+            .unwrap();
+        assert!(TestClient::handshake(stream).await.is_err());
+
+        handle.shutdown();
+        handle.wait().await.unwrap();
+    }
+}
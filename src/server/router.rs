@@ -0,0 +1,535 @@
+//! Multi-database dispatch: a backend that routes each session to one of
+//! several backends by database name.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::error::BoltError;
+use crate::server::auth::AuthInfo;
+use crate::server::backend::{
+    AccessMode, BoltBackend, ResultStream, RoutingTable, SessionConfig, SessionHandle,
+    SessionProperty, TransactionHandle,
+};
+use crate::types::{BoltDict, BoltValue};
+
+/// A [`BoltBackend`] that serves several databases, each from its own
+/// backend.
+///
+/// Database names and aliases are case-insensitive. A request without `db`
+/// goes to the principal's home database, if one was set with
+/// [`home_database`](Self::home_database), and otherwise to the default
+/// database (the first one added, unless changed with
+/// [`default_database`](Self::default_database)). Unknown names fail with
+/// [`BoltError::DatabaseNotFound`] (`Neo.ClientError.Database.DatabaseNotFound`).
+///
+/// Each client session opens a session on a routed backend the first time
+/// it uses that database, created with [`SessionConfig::database`] set to
+/// the database name; authentication and access mode are forwarded to it.
+///
+/// ```rust,no_run
+/// # use boltr::server::BoltBackend;
+/// # fn example(sales: impl BoltBackend, hr: impl BoltBackend) {
+/// use boltr::server::DatabaseRouter;
+///
+/// let router = DatabaseRouter::new()
+///     .database("sales", sales)
+///     .database("hr", hr)
+///     .alias("people", "hr")
+///     .home_database("alice", "hr");
+/// # }
+/// ```
+#[derive(Default)]
+pub struct DatabaseRouter {
+    databases: HashMap<String, Arc<dyn BoltBackend>>,
+    aliases: HashMap<String, String>,
+    default_database: Option<String>,
+    home_databases: HashMap<String, String>,
+    sessions: Mutex<HashMap<SessionHandle, RouterSession>>,
+    next_id: AtomicU64,
+}
+
+/// A routed backend and the session opened on it.
+type Routed = (Arc<dyn BoltBackend>, SessionHandle);
+
+/// Per-client-session routing state.
+struct RouterSession {
+    user_agent: String,
+    auth_info: Option<AuthInfo>,
+    /// Database chosen with `configure_session`; `None` is the home database.
+    selected: Option<String>,
+    access_mode: AccessMode,
+    /// Sessions opened on the routed backends, by database.
+    inner: HashMap<String, SessionHandle>,
+    /// Database of the open explicit transaction.
+    transaction: Option<String>,
+}
+
+impl DatabaseRouter {
+    /// Creates a router without databases. The first
+    /// [`database`](Self::database) added is the default database unless
+    /// [`default_database`](Self::default_database) names another. Database
+    /// names and aliases are matched case-insensitively.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serves database `name` from `backend`. The first database added is
+    /// the default database.
+    pub fn database(mut self, name: &str, backend: impl BoltBackend) -> Self {
+        let name = name.to_ascii_lowercase();
+        self.default_database.get_or_insert_with(|| name.clone());
+        self.databases.insert(name, Arc::new(backend));
+        self
+    }
+
+    /// Makes `alias` another name for `database`.
+    pub fn alias(mut self, alias: &str, database: &str) -> Self {
+        self.aliases
+            .insert(alias.to_ascii_lowercase(), database.to_ascii_lowercase());
+        self
+    }
+
+    /// Sets the database used when a request names none and the principal
+    /// has no home database.
+    pub fn default_database(mut self, name: &str) -> Self {
+        self.default_database = Some(name.to_ascii_lowercase());
+        self
+    }
+
+    /// Sets the database used for `principal` when a request names none.
+    pub fn home_database(mut self, principal: &str, database: &str) -> Self {
+        self.home_databases
+            .insert(principal.to_string(), database.to_ascii_lowercase());
+        self
+    }
+
+    /// Resolves a database name or alias to the name of a configured
+    /// database.
+    fn lookup(&self, name: &str) -> Result<String, BoltError> {
+        let lower = name.to_ascii_lowercase();
+        let canonical = self.aliases.get(&lower).unwrap_or(&lower);
+        if self.databases.contains_key(canonical) {
+            Ok(canonical.clone())
+        } else {
+            Err(BoltError::DatabaseNotFound(format!(
+                "Database does not exist. Database name: '{name}'."
+            )))
+        }
+    }
+
+    /// The database for a request without `db`.
+    fn home(&self, principal: Option<&str>) -> Result<String, BoltError> {
+        let name = principal
+            .and_then(|p| self.home_databases.get(p))
+            .or(self.default_database.as_ref())
+            .ok_or_else(|| BoltError::DatabaseNotFound("no default database".into()))?;
+        self.lookup(name)
+    }
+
+    fn backend(&self, database: &str) -> Arc<dyn BoltBackend> {
+        Arc::clone(&self.databases[database])
+    }
+
+    /// The database the session currently targets.
+    fn current_database(&self, session: &SessionHandle) -> Result<String, BoltError> {
+        let (selected, principal) = self.with_session(session, |s| {
+            (
+                s.selected.clone(),
+                s.auth_info.as_ref().map(|info| info.principal.clone()),
+            )
+        })?;
+        match selected {
+            Some(database) => Ok(database),
+            None => self.home(principal.as_deref()),
+        }
+    }
+
+    fn with_session<T>(
+        &self,
+        session: &SessionHandle,
+        f: impl FnOnce(&mut RouterSession) -> T,
+    ) -> Result<T, BoltError> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let state = sessions
+            .get_mut(session)
+            .ok_or_else(|| BoltError::Session(format!("unknown session: {}", session.0)))?;
+        Ok(f(state))
+    }
+
+    /// Returns the routed backend for `database` and the session opened on
+    /// it for `session`, opening one if needed.
+    async fn inner_session(
+        &self,
+        session: &SessionHandle,
+        database: &str,
+    ) -> Result<Routed, BoltError> {
+        let backend = self.backend(database);
+        let (existing, user_agent, auth_info, access_mode) = self.with_session(session, |s| {
+            (
+                s.inner.get(database).cloned(),
+                s.user_agent.clone(),
+                s.auth_info.clone(),
+                s.access_mode,
+            )
+        })?;
+        if let Some(inner) = existing {
+            return Ok((backend, inner));
+        }
+
+        let config = SessionConfig {
+            user_agent,
+            database: Some(database.to_string()),
+        };
+        let inner = backend.create_session(&config).await?;
+        if let Err(e) = prepare_session(&*backend, &inner, auth_info, access_mode).await {
+            let _ = backend.close_session(&inner).await;
+            return Err(e);
+        }
+        self.with_session(session, |s| {
+            s.inner.insert(database.to_string(), inner.clone());
+        })?;
+        Ok((backend, inner))
+    }
+
+    /// All sessions opened on routed backends for `session`.
+    fn inner_sessions(&self, session: &SessionHandle) -> Result<Vec<Routed>, BoltError> {
+        let inner = self.with_session(session, |s| s.inner.clone())?;
+        Ok(inner
+            .into_iter()
+            .map(|(database, handle)| (self.backend(&database), handle))
+            .collect())
+    }
+
+    /// The routed session holding the open transaction.
+    fn transaction_session(
+        &self,
+        session: &SessionHandle,
+        take: bool,
+    ) -> Result<Routed, BoltError> {
+        let found = self.with_session(session, |s| {
+            let database = if take {
+                s.transaction.take()
+            } else {
+                s.transaction.clone()
+            }?;
+            let inner = s.inner.get(&database)?.clone();
+            Some((database, inner))
+        })?;
+        let (database, inner) =
+            found.ok_or_else(|| BoltError::Transaction("no active transaction".into()))?;
+        Ok((self.backend(&database), inner))
+    }
+}
+
+/// Forwards the client session's identity and access mode to a newly
+/// opened routed session.
+async fn prepare_session(
+    backend: &dyn BoltBackend,
+    session: &SessionHandle,
+    auth_info: Option<AuthInfo>,
+    access_mode: AccessMode,
+) -> Result<(), BoltError> {
+    if let Some(info) = auth_info {
+        backend.set_session_auth(session, info).await?;
+    }
+    if access_mode == AccessMode::Read {
+        backend
+            .configure_session(session, SessionProperty::AccessMode(access_mode))
+            .await?;
+    }
+    Ok(())
+}
+
+#[async_trait::async_trait]
+impl BoltBackend for DatabaseRouter {
+    async fn create_session(&self, config: &SessionConfig) -> Result<SessionHandle, BoltError> {
+        let selected = config
+            .database
+            .as_deref()
+            .map(|name| self.lookup(name))
+            .transpose()?;
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let handle = SessionHandle(format!("router-{id}"));
+        self.sessions.lock().unwrap().insert(
+            handle.clone(),
+            RouterSession {
+                user_agent: config.user_agent.clone(),
+                auth_info: None,
+                selected,
+                access_mode: AccessMode::Write,
+                inner: HashMap::new(),
+                transaction: None,
+            },
+        );
+        Ok(handle)
+    }
+
+    async fn set_session_auth(
+        &self,
+        session: &SessionHandle,
+        auth_info: AuthInfo,
+    ) -> Result<(), BoltError> {
+        self.with_session(session, |s| s.auth_info = Some(auth_info.clone()))?;
+        for (backend, inner) in self.inner_sessions(session)? {
+            backend.set_session_auth(&inner, auth_info.clone()).await?;
+        }
+        Ok(())
+    }
+
+    /// The principal's home database, or the default database.
+    async fn home_database(&self, principal: Option<&str>) -> Result<Option<String>, BoltError> {
+        Ok(self.home(principal).ok())
+    }
+
+    async fn close_session(&self, session: &SessionHandle) -> Result<(), BoltError> {
+        let Some(state) = self.sessions.lock().unwrap().remove(session) else {
+            return Ok(());
+        };
+        let mut result = Ok(());
+        for (database, inner) in state.inner {
+            let closed = self.backend(&database).close_session(&inner).await;
+            if result.is_ok() {
+                result = closed;
+            }
+        }
+        result
+    }
+
+    async fn configure_session(
+        &self,
+        session: &SessionHandle,
+        property: SessionProperty,
+    ) -> Result<(), BoltError> {
+        match property {
+            SessionProperty::Database(name) => {
+                let database = self.lookup(&name)?;
+                self.with_session(session, |s| s.selected = Some(database))
+            }
+            SessionProperty::AccessMode(mode) => {
+                self.with_session(session, |s| s.access_mode = mode)?;
+                for (backend, inner) in self.inner_sessions(session)? {
+                    backend
+                        .configure_session(&inner, SessionProperty::AccessMode(mode))
+                        .await?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    async fn reset_session(&self, session: &SessionHandle) -> Result<(), BoltError> {
+        self.with_session(session, |s| {
+            s.selected = None;
+            s.access_mode = AccessMode::Write;
+            s.transaction = None;
+        })?;
+        for (backend, inner) in self.inner_sessions(session)? {
+            backend.reset_session(&inner).await?;
+        }
+        Ok(())
+    }
+
+    async fn execute(
+        &self,
+        session: &SessionHandle,
+        query: &str,
+        parameters: &HashMap<String, BoltValue>,
+        extra: &BoltDict,
+        transaction: Option<&TransactionHandle>,
+    ) -> Result<ResultStream, BoltError> {
+        let (backend, inner) = if transaction.is_some() {
+            self.transaction_session(session, false)?
+        } else {
+            let database = self.current_database(session)?;
+            self.inner_session(session, &database).await?
+        };
+        backend
+            .execute(&inner, query, parameters, extra, transaction)
+            .await
+    }
+
+    async fn begin_transaction(
+        &self,
+        session: &SessionHandle,
+        extra: &BoltDict,
+    ) -> Result<TransactionHandle, BoltError> {
+        let database = self.current_database(session)?;
+        let (backend, inner) = self.inner_session(session, &database).await?;
+        let transaction = backend.begin_transaction(&inner, extra).await?;
+        self.with_session(session, |s| s.transaction = Some(database))?;
+        Ok(transaction)
+    }
+
+    async fn commit(
+        &self,
+        session: &SessionHandle,
+        transaction: &TransactionHandle,
+    ) -> Result<BoltDict, BoltError> {
+        let (backend, inner) = self.transaction_session(session, true)?;
+        backend.commit(&inner, transaction).await
+    }
+
+    async fn rollback(
+        &self,
+        session: &SessionHandle,
+        transaction: &TransactionHandle,
+    ) -> Result<(), BoltError> {
+        let (backend, inner) = self.transaction_session(session, true)?;
+        backend.rollback(&inner, transaction).await
+    }
+
+    /// Server info of the default database's backend.
+    async fn get_server_info(&self) -> Result<BoltDict, BoltError> {
+        match self.home(None) {
+            Ok(database) => self.backend(&database).get_server_info().await,
+            Err(_) => Ok(BoltDict::new()),
+        }
+    }
+
+    /// Forwards to the backend of the requested (or default) database,
+    /// passing it the resolved database name.
+    async fn route(
+        &self,
+        routing_context: &BoltDict,
+        bookmarks: &[String],
+        db: Option<&str>,
+    ) -> Result<RoutingTable, BoltError> {
+        let database = match db {
+            Some(name) => self.lookup(name)?,
+            None => self.home(None)?,
+        };
+        self.backend(&database)
+            .route(routing_context, bookmarks, Some(&database))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::test_support::MockBackend;
+
+    fn config() -> SessionConfig {
+        SessionConfig {
+            user_agent: "boltr-test/0".into(),
+            database: None,
+        }
+    }
+
+    fn auth(principal: &str) -> AuthInfo {
+        AuthInfo {
+            principal: principal.into(),
+            ..AuthInfo::default()
+        }
+    }
+
+    async fn query(router: &DatabaseRouter, session: &SessionHandle, q: &str) {
+        router
+            .execute(session, q, &HashMap::new(), &BoltDict::new(), None)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn dispatches_by_name_alias_and_home_database() {
+        let sales = MockBackend::default();
+        let hr = MockBackend::default();
+        let router = DatabaseRouter::new()
+            .database("sales", sales.clone())
+            .database("HR", hr.clone())
+            .alias("people", "hr")
+            .home_database("alice", "people");
+
+        let session = router.create_session(&config()).await.unwrap();
+        query(&router, &session, "RETURN 'default'").await;
+        router
+            .configure_session(&session, SessionProperty::Database("People".into()))
+            .await
+            .unwrap();
+        query(&router, &session, "RETURN 'alias'").await;
+
+        let err = router
+            .configure_session(&session, SessionProperty::Database("nope".into()))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_failure_metadata()["code"],
+            BoltValue::String("Neo.ClientError.Database.DatabaseNotFound".into())
+        );
+
+        let other = router.create_session(&config()).await.unwrap();
+        router
+            .set_session_auth(&other, auth("alice"))
+            .await
+            .unwrap();
+        query(&router, &other, "RETURN 'home'").await;
+
+        assert_eq!(*sales.queries.lock().unwrap(), ["RETURN 'default'"]);
+        assert_eq!(
+            *hr.queries.lock().unwrap(),
+            ["RETURN 'alias'", "RETURN 'home'"]
+        );
+
+        // Closing a client session closes every routed session it opened.
+        router.close_session(&session).await.unwrap();
+        assert_eq!(sales.closed.lock().unwrap().len(), 1);
+        assert_eq!(hr.closed.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn transactions_stay_on_their_database() {
+        let sales = MockBackend::default();
+        let hr = MockBackend::default();
+        let router = DatabaseRouter::new()
+            .database("sales", sales.clone())
+            .database("hr", hr.clone());
+
+        let session = router.create_session(&config()).await.unwrap();
+        router
+            .configure_session(&session, SessionProperty::Database("hr".into()))
+            .await
+            .unwrap();
+        let tx = router
+            .begin_transaction(&session, &BoltDict::new())
+            .await
+            .unwrap();
+        router
+            .execute(
+                &session,
+                "RETURN 1",
+                &HashMap::new(),
+                &BoltDict::new(),
+                Some(&tx),
+            )
+            .await
+            .unwrap();
+        router.rollback(&session, &tx).await.unwrap();
+        assert_eq!(*hr.queries.lock().unwrap(), ["RETURN 1"]);
+        assert_eq!(hr.rolled_back.lock().unwrap().len(), 1);
+
+        // RESET returns to the default database.
+        router.reset_session(&session).await.unwrap();
+        query(&router, &session, "RETURN 2").await;
+        assert_eq!(*sales.queries.lock().unwrap(), ["RETURN 2"]);
+    }
+
+    #[tokio::test]
+    async fn route_is_forwarded_per_database() {
+        let router = DatabaseRouter::new()
+            .database("sales", MockBackend::default())
+            .alias("crm", "sales");
+
+        let table = router
+            .route(&BoltDict::new(), &[], Some("CRM"))
+            .await
+            .unwrap();
+        assert_eq!(table.db, "sales");
+        let table = router.route(&BoltDict::new(), &[], None).await.unwrap();
+        assert_eq!(table.db, "sales");
+        assert!(matches!(
+            router.route(&BoltDict::new(), &[], Some("hr")).await,
+            Err(BoltError::DatabaseNotFound(_))
+        ));
+    }
+}
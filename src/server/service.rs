@@ -0,0 +1,120 @@
+//! Serving individual, already-accepted streams.
+
+use std::sync::Arc;
+
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::server::backend::BoltBackend;
+use crate::server::context::{ServerContext, Transport};
+use crate::server::peer::PeerInfo;
+use crate::server::session_manager::SessionManager;
+
+/// A configured Bolt server without an accept loop, created with
+/// [`BoltServer::into_service`](crate::server::BoltServer::into_service).
+///
+/// Cloning is cheap; all clones share the backend, session manager and
+/// authentication settings.
+///
+/// ```rust,no_run
+/// use boltr::server::{BoltServer, PeerInfo};
+/// # use boltr::server::BoltBackend;
+///
+/// # async fn example(backend: impl BoltBackend) {
+/// let service = BoltServer::builder(backend).into_service();
+///
+/// let (client, server) = tokio::io::duplex(64 * 1024);
+/// let peer = PeerInfo::new("127.0.0.1:0".parse().unwrap());
+/// tokio::spawn(async move { service.serve_connection(server, peer).await });
+/// # drop(client);
+/// # }
+/// ```
+pub struct BoltService<B: BoltBackend> {
+    ctx: Arc<ServerContext<B>>,
+}
+
+impl<B: BoltBackend> Clone for BoltService<B> {
+    fn clone(&self) -> Self {
+        Self {
+            ctx: self.ctx.clone(),
+        }
+    }
+}
+
+impl<B: BoltBackend> BoltService<B> {
+    pub(crate) fn new(ctx: Arc<ServerContext<B>>) -> Self {
+        Self { ctx }
+    }
+
+    /// Runs the Bolt protocol on a single stream until the client disconnects.
+    ///
+    /// Performs the TLS handshake (if configured), the Bolt handshake and the
+    /// message loop; the session is closed when the connection ends.
+    pub async fn serve_connection<S>(&self, stream: S, peer: impl Into<PeerInfo>)
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        self.ctx.counters.connection_accepted();
+        self.ctx
+            .clone()
+            .serve_stream(stream, peer.into(), Transport::Bolt)
+            .await;
+    }
+
+    /// Returns the session manager shared by all connections of this service.
+    pub fn session_manager(&self) -> &Arc<SessionManager> {
+        &self.ctx.session_manager
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::message::ServerMessage;
+    use crate::server::BoltServer;
+    use crate::server::test_support::{MockBackend, TestClient, run};
+
+    #[tokio::test]
+    async fn serve_connection_over_duplex() {
+        let backend = MockBackend::default();
+        let service = BoltServer::builder(backend.clone()).into_service();
+
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        let peer: std::net::SocketAddr = "127.0.0.1:4000".parse().unwrap();
+        let task = tokio::spawn({
+            let service = service.clone();
+            async move { service.serve_connection(server, peer).await }
+        });
+
+        let mut client = TestClient::handshake(client).await.unwrap();
+        client.authenticate().await.unwrap();
+        let reply = client.request(run("RETURN 1")).await.unwrap();
+        assert!(matches!(reply, ServerMessage::Success { .. }));
+        assert_eq!(service.session_manager().count(), 1);
+
+        drop(client);
+        task.await.unwrap();
+        assert_eq!(*backend.closed.lock().unwrap(), vec!["s0"]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn serve_unix_listener() {
+        let path = std::env::temp_dir().join(format!("boltr-{}.sock", uuid::Uuid::new_v4()));
+        let listener = tokio::net::UnixListener::bind(&path).unwrap();
+        let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(
+            BoltServer::builder(MockBackend::default())
+                .shutdown(async move { drop(stop_rx.await) })
+                .serve_listener(listener),
+        );
+
+        let stream = tokio::net::UnixStream::connect(&path).await.unwrap();
+        let mut client = TestClient::handshake(stream).await.unwrap();
+        client.authenticate().await.unwrap();
+        let reply = client.request(run("RETURN 1")).await.unwrap();
+        assert!(matches!(reply, ServerMessage::Success { .. }));
+
+        stop_tx.send(()).unwrap();
+        server.await.unwrap().unwrap();
+        let _ = std::fs::remove_file(&path);
+    }
+}
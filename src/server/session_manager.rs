@@ -12,6 +12,8 @@ use crate::server::SessionHandle;
 pub struct SessionState {
     pub handle: SessionHandle,
     pub peer_addr: SocketAddr,
+    /// Authenticated principal, set on LOGON and cleared on LOGOFF.
+    pub principal: Option<String>,
     pub created_at: Instant,
     pub last_active: Instant,
 }
@@ -20,6 +22,8 @@ pub struct SessionState {
 pub struct SessionManager {
     sessions: RwLock<HashMap<String, SessionState>>,
     max_sessions: Option<usize>,
+    max_per_ip: Option<usize>,
+    max_per_principal: Option<usize>,
 }
 
 impl SessionManager {
@@ -27,10 +31,25 @@ impl SessionManager {
         Self {
             sessions: RwLock::new(HashMap::new()),
             max_sessions,
+            max_per_ip: None,
+            max_per_principal: None,
         }
     }
 
-    /// Registers a new session. Fails if the capacity limit is reached.
+    /// Caps the sessions a single remote IP may hold.
+    pub fn with_max_per_ip(mut self, limit: usize) -> Self {
+        self.max_per_ip = Some(limit);
+        self
+    }
+
+    /// Caps the sessions a single authenticated principal may hold.
+    pub fn with_max_per_principal(mut self, limit: usize) -> Self {
+        self.max_per_principal = Some(limit);
+        self
+    }
+
+    /// Registers a new session. Fails if the total or per-IP capacity limit
+    /// is reached.
     pub fn register(&self, handle: SessionHandle, peer_addr: SocketAddr) -> Result<(), BoltError> {
         let mut sessions = self.sessions.write().unwrap();
         if let Some(limit) = self.max_sessions
@@ -40,12 +59,25 @@ impl SessionManager {
                 "max sessions ({limit}) reached"
             )));
         }
+        if let Some(limit) = self.max_per_ip {
+            let ip = peer_addr.ip().to_canonical();
+            let count = sessions
+                .values()
+                .filter(|s| s.peer_addr.ip().to_canonical() == ip)
+                .count();
+            if count >= limit {
+                return Err(BoltError::SessionLimit(format!(
+                    "max sessions per IP ({limit}) reached for {ip}"
+                )));
+            }
+        }
         let now = Instant::now();
         sessions.insert(
             handle.0.clone(),
             SessionState {
                 handle,
                 peer_addr,
+                principal: None,
                 created_at: now,
                 last_active: now,
             },
@@ -53,6 +85,28 @@ impl SessionManager {
         Ok(())
     }
 
+    /// Records the principal a session authenticated as, or clears it with
+    /// `None`. Fails if the principal already holds the maximum number of
+    /// sessions.
+    pub fn set_principal(&self, id: &str, principal: Option<&str>) -> Result<(), BoltError> {
+        let mut sessions = self.sessions.write().unwrap();
+        if let (Some(limit), Some(principal)) = (self.max_per_principal, principal) {
+            let count = sessions
+                .iter()
+                .filter(|(other, s)| *other != id && s.principal.as_deref() == Some(principal))
+                .count();
+            if count >= limit {
+                return Err(BoltError::SessionLimit(format!(
+                    "max sessions per principal ({limit}) reached for {principal}"
+                )));
+            }
+        }
+        if let Some(state) = sessions.get_mut(id) {
+            state.principal = principal.map(String::from);
+        }
+        Ok(())
+    }
+
     /// Removes a session.
     pub fn remove(&self, id: &str) {
         self.sessions.write().unwrap().remove(id);
@@ -116,4 +170,28 @@ mod tests {
         let result = mgr.register(SessionHandle("s2".into()), addr());
         assert!(result.is_err());
     }
+
+    #[test]
+    fn per_ip_and_per_principal_limits() {
+        let mgr = SessionManager::new(None)
+            .with_max_per_ip(1)
+            .with_max_per_principal(1);
+        let other: SocketAddr = "127.0.0.2:9999".parse().unwrap();
+        mgr.register(SessionHandle("s1".into()), addr()).unwrap();
+        assert!(matches!(
+            mgr.register(SessionHandle("s2".into()), addr()),
+            Err(BoltError::SessionLimit(_))
+        ));
+        mgr.register(SessionHandle("s2".into()), other).unwrap();
+
+        mgr.set_principal("s1", Some("alice")).unwrap();
+        // Re-authenticating the same session does not count twice.
+        mgr.set_principal("s1", Some("alice")).unwrap();
+        assert!(matches!(
+            mgr.set_principal("s2", Some("alice")),
+            Err(BoltError::SessionLimit(_))
+        ));
+        mgr.set_principal("s1", None).unwrap();
+        mgr.set_principal("s2", Some("alice")).unwrap();
+    }
 }
@@ -0,0 +1,386 @@
+//! Single-port protocol detection: raw Bolt, TLS, WebSocket and plain HTTP.
+//!
+//! Used by [`BoltServer::serve_auto`](crate::server::BoltServer::serve_auto),
+//! which inspects the first bytes of each connection:
+//!
+//! - `60 60 B0 17` (Bolt magic): raw Bolt.
+//! - `16 03` (a TLS handshake record): TLS, if a
+//!   [`TlsConfig`](crate::server::TlsConfig) is set. Detection then runs
+//!   again on the decrypted stream.
+//! - An HTTP request with `Upgrade: websocket`: Bolt over WebSocket.
+//! - Any other HTTP request: the configured [`HttpHandler`], if any.
+//!
+//! So with TLS configured one port serves Bolt, Bolt+TLS, WS, WSS, HTTP and
+//! HTTPS side by side.
+
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use bytes::{Buf, BytesMut};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, ReadBuf};
+
+use crate::server::peer::PeerInfo;
+use crate::version::BOLT_MAGIC;
+
+/// Upper bound on buffered HTTP request head bytes while sniffing.
+const MAX_HTTP_HEAD: usize = 8 * 1024;
+
+/// Content type (handshake) and major version of a TLS record header.
+const TLS_HANDSHAKE: [u8; 2] = [0x16, 0x03];
+
+/// Handles plain HTTP requests (health checks, metrics, ...) arriving on a
+/// port served with [`BoltServer::serve_auto`](crate::server::BoltServer::serve_auto).
+///
+/// ```rust
+/// use boltr::server::PeerInfo;
+/// use boltr::server::sniff::{HttpHandler, SniffedStream};
+/// use tokio::io::AsyncWriteExt;
+///
+/// struct Health;
+///
+/// #[async_trait::async_trait]
+/// impl HttpHandler for Health {
+///     async fn handle(&self, mut stream: SniffedStream, _peer: PeerInfo) {
+///         let _ = stream
+///             .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok")
+///             .await;
+///     }
+/// }
+/// ```
+#[async_trait::async_trait]
+pub trait HttpHandler: Send + Sync + 'static {
+    /// Handles one HTTP connection. The stream replays the request from its
+    /// first byte; it is closed when this returns.
+    async fn handle(&self, stream: SniffedStream, peer: PeerInfo);
+}
+
+/// Object-safe bound for the streams behind [`SniffedStream`].
+trait Io: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Io for T {}
+
+/// A connection handed to an [`HttpHandler`], positioned at the start of
+/// the HTTP request (already-sniffed bytes are replayed).
+pub struct SniffedStream {
+    inner: Box<dyn Io>,
+}
+
+impl AsyncRead for SniffedStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for SniffedStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+/// A stream that replays bytes read ahead during sniffing before reading
+/// from the underlying stream.
+pub(crate) struct Rewind<S> {
+    prefix: BytesMut,
+    inner: S,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin + Send + 'static> Rewind<S> {
+    pub(crate) fn new(inner: S) -> Self {
+        Self {
+            prefix: BytesMut::new(),
+            inner,
+        }
+    }
+
+    /// The bytes read ahead so far.
+    pub(crate) fn peeked(&self) -> &[u8] {
+        &self.prefix
+    }
+
+    /// Reads ahead until at least `n` bytes are buffered.
+    /// Returns `false` if the stream ends first.
+    pub(crate) async fn peek(&mut self, n: usize) -> io::Result<bool> {
+        while self.prefix.len() < n {
+            if self.inner.read_buf(&mut self.prefix).await? == 0 {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    pub(crate) fn into_sniffed(self) -> SniffedStream {
+        SniffedStream {
+            inner: Box::new(self),
+        }
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for Rewind<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        if !self.prefix.is_empty() {
+            let n = self.prefix.len().min(buf.remaining());
+            buf.put_slice(&self.prefix[..n]);
+            self.prefix.advance(n);
+            return Poll::Ready(Ok(()));
+        }
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for Rewind<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+/// Protocol spoken on a (decrypted) connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Protocol {
+    Bolt,
+    Tls,
+    WebSocket,
+    Http,
+    Unknown,
+}
+
+/// Reads ahead far enough to tell Bolt, TLS, WebSocket and HTTP apart.
+pub(crate) async fn detect<S>(stream: &mut Rewind<S>) -> io::Result<Protocol>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    if !stream.peek(4).await? {
+        return Ok(Protocol::Unknown);
+    }
+    if stream.peeked()[..4] == BOLT_MAGIC {
+        return Ok(Protocol::Bolt);
+    }
+    if stream.peeked()[..2] == TLS_HANDSHAKE {
+        return Ok(Protocol::Tls);
+    }
+    if !stream.peeked()[..4]
+        .iter()
+        .all(|b| b.is_ascii_uppercase() || *b == b' ')
+    {
+        return Ok(Protocol::Unknown);
+    }
+
+    // Looks like an HTTP method: read the request head.
+    loop {
+        if let Some(end) = find(stream.peeked(), b"\r\n\r\n") {
+            let head = &stream.peeked()[..end];
+            return Ok(if is_websocket_upgrade(head) {
+                Protocol::WebSocket
+            } else {
+                Protocol::Http
+            });
+        }
+        let len = stream.peeked().len();
+        if len >= MAX_HTTP_HEAD || !stream.peek(len + 1).await? {
+            return Ok(Protocol::Http);
+        }
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// Whether an HTTP request head is a `GET` carrying `Upgrade: websocket`.
+fn is_websocket_upgrade(head: &[u8]) -> bool {
+    let Ok(head) = std::str::from_utf8(head) else {
+        return false;
+    };
+    let mut lines = head.split("\r\n");
+    let is_get = lines.next().is_some_and(|line| line.starts_with("GET "));
+    is_get
+        && lines.any(|line| {
+            line.split_once(':').is_some_and(|(name, value)| {
+                name.trim().eq_ignore_ascii_case("upgrade")
+                    && value.to_ascii_lowercase().contains("websocket")
+            })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncWriteExt;
+
+    async fn detect_bytes(bytes: &[u8]) -> Protocol {
+        let (mut client, server) = tokio::io::duplex(MAX_HTTP_HEAD * 2);
+        client.write_all(bytes).await.unwrap();
+        drop(client);
+        detect(&mut Rewind::new(server)).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn detects_protocols() {
+        assert_eq!(
+            detect_bytes(&[0x60, 0x60, 0xB0, 0x17, 0, 0]).await,
+            Protocol::Bolt
+        );
+        assert_eq!(
+            detect_bytes(b"GET / HTTP/1.1\r\nHost: x\r\nupgrade: WebSocket\r\n\r\n").await,
+            Protocol::WebSocket
+        );
+        assert_eq!(
+            detect_bytes(b"GET /health HTTP/1.1\r\nHost: x\r\n\r\n").await,
+            Protocol::Http
+        );
+        assert_eq!(
+            detect_bytes(&[0x16, 0x03, 0x01, 0x02, 0x00]).await,
+            Protocol::Tls
+        );
+        assert_eq!(
+            detect_bytes(&[0x00, 0x01, 0x02, 0x03]).await,
+            Protocol::Unknown
+        );
+        assert_eq!(detect_bytes(&[0x60]).await, Protocol::Unknown);
+    }
+
+    #[tokio::test]
+    async fn rewind_replays_peeked_bytes() {
+        let (mut client, server) = tokio::io::duplex(64);
+        client.write_all(b"GET /x").await.unwrap();
+        drop(client);
+
+        let mut stream = Rewind::new(server);
+        assert!(stream.peek(3).await.unwrap());
+        let mut all = Vec::new();
+        stream.read_to_end(&mut all).await.unwrap();
+        assert_eq!(all, b"GET /x");
+    }
+
+    struct Health;
+
+    #[async_trait::async_trait]
+    impl HttpHandler for Health {
+        async fn handle(&self, mut stream: SniffedStream, _peer: PeerInfo) {
+            let _ = stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok")
+                .await;
+        }
+    }
+
+    #[tokio::test]
+    async fn one_port_serves_bolt_websocket_and_http() {
+        use crate::message::ServerMessage;
+        use crate::server::BoltServer;
+        use crate::server::test_support::{MockBackend, TestClient, run};
+
+        let handle = BoltServer::builder(MockBackend::default())
+            .http_handler(Health)
+            .bind_auto("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let addr = handle.local_addr();
+
+        let mut bolt = TestClient::connect(addr).await.unwrap();
+        bolt.authenticate().await.unwrap();
+        let reply = bolt.request(run("RETURN 1")).await.unwrap();
+        assert!(matches!(reply, ServerMessage::Success { .. }));
+
+        let (ws, _) = tokio_tungstenite::connect_async(format!("ws://{addr}"))
+            .await
+            .unwrap();
+        let mut ws = TestClient::handshake(crate::ws::WsStream::new(ws))
+            .await
+            .unwrap();
+        ws.authenticate().await.unwrap();
+
+        let mut http = tokio::net::TcpStream::connect(addr).await.unwrap();
+        http.write_all(b"GET /health HTTP/1.1\r\nHost: x\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        http.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"), "{response}");
+        assert!(response.ends_with("ok"));
+
+        handle.shutdown();
+        handle.wait().await.unwrap();
+    }
+
+    #[cfg(feature = "tls")]
+    #[tokio::test]
+    async fn one_port_serves_plaintext_and_tls() {
+        use std::sync::Arc;
+
+        use rustls_pki_types::ServerName;
+        use tokio_rustls::TlsConnector;
+        use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+
+        use crate::message::ServerMessage;
+        use crate::server::test_support::{MockBackend, TestClient, run};
+        use crate::server::{BoltServer, TlsConfig};
+
+        let identity = rcgen::generate_simple_self_signed(vec!["localhost".into()]).unwrap();
+        let tls = TlsConfig::from_pem(
+            identity.cert.pem().as_bytes(),
+            identity.signing_key.serialize_pem().as_bytes(),
+        )
+        .unwrap();
+        let handle = BoltServer::builder(MockBackend::default())
+            .tls(tls)
+            .bind_auto("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let addr = handle.local_addr();
+
+        let mut plain = TestClient::connect(addr).await.unwrap();
+        plain.authenticate().await.unwrap();
+        let reply = plain.request(run("RETURN 1")).await.unwrap();
+        assert!(matches!(reply, ServerMessage::Success { .. }));
+
+        let mut roots = RootCertStore::empty();
+        roots.add(identity.cert.der().clone()).unwrap();
+        let config = ClientConfig::builder()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        let tcp = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let stream = TlsConnector::from(Arc::new(config))
+            .connect(ServerName::try_from("localhost").unwrap(), tcp)
+            .await
+            .unwrap();
+        let mut encrypted = TestClient::handshake(stream).await.unwrap();
+        encrypted.authenticate().await.unwrap();
+        let reply = encrypted.request(run("RETURN 1")).await.unwrap();
+        assert!(matches!(reply, ServerMessage::Success { .. }));
+
+        handle.shutdown();
+        handle.wait().await.unwrap();
+    }
+}
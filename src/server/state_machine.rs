@@ -127,6 +127,9 @@ impl ConnectionState {
         match msg {
             ClientMessage::Goodbye => Self::Defunct,
             ClientMessage::Reset => Self::Defunct, // RESET failure is fatal
+            // A failed HELLO or LOGON closes the connection; RESET must not
+            // lead an unauthenticated client to READY.
+            ClientMessage::Hello { .. } | ClientMessage::Logon { .. } => Self::Defunct,
             _ => Self::Failed,
         }
     }
@@ -233,6 +236,7 @@ mod tests {
         assert!(s.accepts(&ClientMessage::Goodbye));
         assert!(!s.accepts(&run()));
         assert!(!s.accepts(&pull()));
+        assert!(!s.accepts(&ClientMessage::Logoff));
     }
 
     #[test]
@@ -241,6 +245,18 @@ mod tests {
         assert_eq!(s.transition_failure(&run()), ConnectionState::Failed);
     }
 
+    #[test]
+    fn failed_authentication_is_fatal() {
+        assert_eq!(
+            ConnectionState::Negotiation.transition_failure(&hello()),
+            ConnectionState::Defunct
+        );
+        assert_eq!(
+            ConnectionState::Authentication.transition_failure(&logon()),
+            ConnectionState::Defunct
+        );
+    }
+
     #[test]
     fn reset_from_failed() {
         let s = ConnectionState::Failed;
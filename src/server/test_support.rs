@@ -0,0 +1,292 @@
+//! Shared fixtures for server tests: an in-memory backend and a raw client.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use bytes::BytesMut;
+use tokio::io::{AsyncRead, AsyncWrite, ReadHalf, WriteHalf};
+
+use crate::chunk::{ChunkReader, ChunkWriter};
+use crate::error::BoltError;
+use crate::message::decode::decode_server_message;
+use crate::message::encode::encode_client_message;
+use crate::message::{ClientMessage, ServerMessage};
+use crate::server::backend::{
+    AccessMode, AuthCredentials, BoltBackend, BoltRecord, ResultMetadata, ResultStream,
+    RoutingServer, RoutingTable, SessionConfig, SessionHandle, SessionProperty, TransactionHandle,
+};
+use crate::server::handshake::{client_handshake, default_client_proposals};
+use crate::server::peer::PeerInfo;
+use crate::types::{BoltDict, BoltValue, QueryStatistics, QueryType, ResultSummary};
+
+/// Backend that answers every query with a single `n = 1` row (`RANGE <k>`
+/// with rows 1 to k) and records lifecycle calls for assertions. Clones share state, so a test can keep one
+/// clone for assertions while the server owns another.
+#[derive(Clone, Default)]
+pub(crate) struct MockBackend {
+    next_id: Arc<AtomicU64>,
+    pub(crate) closed: Arc<Mutex<Vec<String>>>,
+    pub(crate) rolled_back: Arc<Mutex<Vec<String>>>,
+    pub(crate) databases: Arc<Mutex<HashMap<String, String>>>,
+    /// Every executed query, in order.
+    pub(crate) queries: Arc<Mutex<Vec<String>>>,
+    /// Number of upcoming queries that fail with a transient deadlock.
+    pub(crate) transient_failures: Arc<AtomicU64>,
+    /// Sessions in read mode; `CREATE` queries fail in them.
+    read_sessions: Arc<Mutex<HashSet<String>>>,
+    /// Ignore `SessionProperty::AccessMode`, so that reads can write.
+    pub(crate) ignores_access_mode: bool,
+}
+
+#[async_trait::async_trait]
+impl BoltBackend for MockBackend {
+    async fn create_session(&self, _config: &SessionConfig) -> Result<SessionHandle, BoltError> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        Ok(SessionHandle(format!("s{id}")))
+    }
+
+    async fn close_session(&self, session: &SessionHandle) -> Result<(), BoltError> {
+        self.closed.lock().unwrap().push(session.0.clone());
+        Ok(())
+    }
+
+    async fn configure_session(
+        &self,
+        session: &SessionHandle,
+        property: SessionProperty,
+    ) -> Result<(), BoltError> {
+        match property {
+            SessionProperty::Database(db) => {
+                self.databases.lock().unwrap().insert(session.0.clone(), db);
+            }
+            SessionProperty::AccessMode(_) if self.ignores_access_mode => {}
+            SessionProperty::AccessMode(AccessMode::Read) => {
+                self.read_sessions.lock().unwrap().insert(session.0.clone());
+            }
+            SessionProperty::AccessMode(AccessMode::Write) => {
+                self.read_sessions.lock().unwrap().remove(&session.0);
+            }
+        }
+        Ok(())
+    }
+
+    async fn reset_session(&self, session: &SessionHandle) -> Result<(), BoltError> {
+        self.read_sessions.lock().unwrap().remove(&session.0);
+        Ok(())
+    }
+
+    async fn execute(
+        &self,
+        session: &SessionHandle,
+        query: &str,
+        _parameters: &HashMap<String, BoltValue>,
+        _extra: &BoltDict,
+        _transaction: Option<&TransactionHandle>,
+    ) -> Result<ResultStream, BoltError> {
+        if query.starts_with("CREATE") && self.read_sessions.lock().unwrap().contains(&session.0) {
+            return Err(BoltError::AccessMode(
+                "writing in read access mode not allowed".into(),
+            ));
+        }
+        self.queries.lock().unwrap().push(query.to_string());
+        let fail = |n: u64| n.checked_sub(1);
+        if self
+            .transient_failures
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, fail)
+            .is_ok()
+        {
+            return Err(BoltError::Query {
+                code: "Neo.TransientError.Transaction.DeadlockDetected".into(),
+                message: "deadlock detected".into(),
+            });
+        }
+        if let Some(ms) = query.strip_prefix("SLEEP ") {
+            let ms = ms.parse().unwrap_or(0);
+            tokio::time::sleep(std::time::Duration::from_millis(ms)).await;
+        }
+        Ok(ResultStream {
+            metadata: ResultMetadata {
+                columns: vec!["n".into()],
+                extra: BoltDict::new(),
+            },
+            records: (1..=query
+                .strip_prefix("RANGE ")
+                .map_or(1, |k| k.parse().unwrap_or(0)))
+                .map(|n| BoltRecord {
+                    values: vec![BoltValue::Integer(n)],
+                })
+                .collect(),
+            summary: if query.starts_with("CREATE") {
+                ResultSummary {
+                    query_type: Some(QueryType::Write),
+                    counters: QueryStatistics {
+                        nodes_created: 1,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                }
+            } else {
+                ResultSummary {
+                    query_type: Some(QueryType::Read),
+                    ..Default::default()
+                }
+            },
+        })
+    }
+
+    async fn begin_transaction(
+        &self,
+        session: &SessionHandle,
+        _extra: &BoltDict,
+    ) -> Result<TransactionHandle, BoltError> {
+        Ok(TransactionHandle(format!("{}-tx", session.0)))
+    }
+
+    async fn commit(
+        &self,
+        _session: &SessionHandle,
+        _transaction: &TransactionHandle,
+    ) -> Result<BoltDict, BoltError> {
+        Ok(BoltDict::new())
+    }
+
+    async fn rollback(
+        &self,
+        _session: &SessionHandle,
+        transaction: &TransactionHandle,
+    ) -> Result<(), BoltError> {
+        self.rolled_back.lock().unwrap().push(transaction.0.clone());
+        Ok(())
+    }
+
+    async fn get_server_info(&self) -> Result<BoltDict, BoltError> {
+        Ok(BoltDict::from([(
+            "server".to_string(),
+            BoltValue::String("boltr-test/0".to_string()),
+        )]))
+    }
+
+    async fn route(
+        &self,
+        _routing_context: &BoltDict,
+        _bookmarks: &[String],
+        db: Option<&str>,
+    ) -> Result<RoutingTable, BoltError> {
+        Ok(RoutingTable {
+            ttl: 300,
+            db: db.unwrap_or("neo4j").to_string(),
+            servers: vec![RoutingServer {
+                addresses: vec!["localhost:7687".into()],
+                role: "WRITE".into(),
+            }],
+        })
+    }
+}
+
+/// Minimal raw-protocol client for driving a server in tests.
+pub(crate) struct TestClient<S> {
+    reader: ChunkReader<ReadHalf<S>>,
+    writer: ChunkWriter<WriteHalf<S>>,
+}
+
+impl TestClient<tokio::net::TcpStream> {
+    pub(crate) async fn connect(addr: std::net::SocketAddr) -> Result<Self, BoltError> {
+        Self::handshake(tokio::net::TcpStream::connect(addr).await?).await
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> TestClient<S> {
+    pub(crate) async fn handshake(mut stream: S) -> Result<Self, BoltError> {
+        client_handshake(&mut stream, &default_client_proposals()).await?;
+        let (rh, wh) = tokio::io::split(stream);
+        Ok(Self {
+            reader: ChunkReader::new(rh),
+            writer: ChunkWriter::new(wh),
+        })
+    }
+
+    pub(crate) async fn send(&mut self, msg: ClientMessage) -> Result<(), BoltError> {
+        let mut buf = BytesMut::new();
+        encode_client_message(&mut buf, &msg);
+        self.writer.write_message(&buf).await?;
+        self.writer.flush().await
+    }
+
+    pub(crate) async fn recv(&mut self) -> Result<ServerMessage, BoltError> {
+        loop {
+            let data = self.reader.read_message().await?;
+            if !data.is_empty() {
+                return decode_server_message(&data);
+            }
+        }
+    }
+
+    pub(crate) async fn request(&mut self, msg: ClientMessage) -> Result<ServerMessage, BoltError> {
+        self.send(msg).await?;
+        self.recv().await
+    }
+
+    /// Sends HELLO and LOGON (scheme `none`), expecting SUCCESS for both.
+    pub(crate) async fn authenticate(&mut self) -> Result<(), BoltError> {
+        let hello = self.request(hello()).await?;
+        assert!(matches!(hello, ServerMessage::Success { .. }), "{hello:?}");
+        let logon = self.request(logon("none")).await?;
+        assert!(matches!(logon, ServerMessage::Success { .. }), "{logon:?}");
+        Ok(())
+    }
+}
+
+pub(crate) fn hello() -> ClientMessage {
+    ClientMessage::Hello {
+        extra: BoltDict::from([(
+            "user_agent".to_string(),
+            BoltValue::String("boltr-test/0".to_string()),
+        )]),
+    }
+}
+
+pub(crate) fn logon(scheme: &str) -> ClientMessage {
+    ClientMessage::Logon {
+        auth: BoltDict::from([("scheme".to_string(), BoltValue::String(scheme.into()))]),
+    }
+}
+
+pub(crate) fn run(query: &str) -> ClientMessage {
+    ClientMessage::Run {
+        query: query.to_string(),
+        parameters: BoltDict::new(),
+        extra: BoltDict::new(),
+    }
+}
+
+pub(crate) fn pull() -> ClientMessage {
+    ClientMessage::Pull {
+        extra: BoltDict::from([("n".to_string(), BoltValue::Integer(-1))]),
+    }
+}
+
+/// Returns the `code` of a FAILURE, or `None` for any other message.
+pub(crate) fn failure_code(msg: &ServerMessage) -> Option<&str> {
+    match msg {
+        ServerMessage::Failure { metadata } => match metadata.get("code") {
+            Some(BoltValue::String(code)) => Some(code),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Credentials as a LOGON with the given fields would produce them.
+pub(crate) fn credentials(
+    scheme: &str,
+    principal: Option<&str>,
+    secret: Option<&str>,
+) -> AuthCredentials {
+    AuthCredentials {
+        scheme: scheme.to_string(),
+        principal: principal.map(String::from),
+        credentials: secret.map(String::from),
+        peer: PeerInfo::new("127.0.0.1:9999".parse().unwrap()),
+    }
+}
@@ -0,0 +1,432 @@
+//! TLS configuration for the Bolt server (feature `tls`).
+//!
+//! Server certificates are served through a [`CertificateResolver`], which can
+//! be updated while the server is running: new handshakes pick up the new
+//! certificate, established connections are unaffected.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use rustls_pki_types::pem::PemObject;
+use rustls_pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::TlsAcceptor;
+use tokio_rustls::rustls::crypto::CryptoProvider;
+use tokio_rustls::rustls::server::{ClientHello, ResolvesServerCert, WebPkiClientVerifier};
+use tokio_rustls::rustls::sign::CertifiedKey;
+use tokio_rustls::rustls::{RootCertStore, ServerConfig};
+
+use crate::error::BoltError;
+use crate::server::peer::PeerCertificate;
+
+/// Whether clients must present a certificate during the TLS handshake.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClientAuth {
+    /// Clients may present a certificate; if they do, it must verify.
+    Optional,
+    /// Clients without a valid certificate are rejected during the handshake.
+    Required,
+}
+
+/// Reloadable server certificates with SNI-based selection.
+///
+/// Certificates registered for a hostname are served to clients that send a
+/// matching SNI name (`*.example.com` entries match a single leading label);
+/// all other clients get the default certificate. Every setter takes effect
+/// for the next handshake.
+///
+/// ```rust,no_run
+/// # fn example(cert: &[u8], key: &[u8], new_cert: &[u8], new_key: &[u8])
+/// #     -> Result<(), boltr::error::BoltError> {
+/// use boltr::server::TlsConfig;
+///
+/// let tls = TlsConfig::from_pem(cert, key)?;
+/// let certificates = tls.resolver().clone();
+/// // ... hand `tls` to `BoltServer::tls()`, later rotate:
+/// certificates.set_default_pem(new_cert, new_key)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct CertificateResolver {
+    provider: Arc<CryptoProvider>,
+    default: RwLock<Option<Arc<CertifiedKey>>>,
+    by_host: RwLock<HashMap<String, Arc<CertifiedKey>>>,
+}
+
+impl CertificateResolver {
+    /// Creates an empty resolver. Handshakes fail until a certificate is set.
+    pub fn new() -> Self {
+        Self {
+            provider: ServerConfig::builder().crypto_provider().clone(),
+            default: RwLock::new(None),
+            by_host: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Creates a resolver serving the given PEM certificate chain and key by default.
+    pub fn from_pem(cert_pem: &[u8], key_pem: &[u8]) -> Result<Self, BoltError> {
+        let resolver = Self::new();
+        resolver.set_default_pem(cert_pem, key_pem)?;
+        Ok(resolver)
+    }
+
+    /// Replaces the default certificate.
+    pub fn set_default_pem(&self, cert_pem: &[u8], key_pem: &[u8]) -> Result<(), BoltError> {
+        let key = self.certified_key(cert_pem, key_pem)?;
+        *self.default.write().unwrap() = Some(key);
+        Ok(())
+    }
+
+    /// Adds or replaces the certificate served for `hostname`.
+    pub fn set_host_pem(
+        &self,
+        hostname: &str,
+        cert_pem: &[u8],
+        key_pem: &[u8],
+    ) -> Result<(), BoltError> {
+        let key = self.certified_key(cert_pem, key_pem)?;
+        self.by_host
+            .write()
+            .unwrap()
+            .insert(hostname.to_ascii_lowercase(), key);
+        Ok(())
+    }
+
+    /// Removes the certificate for `hostname`. Returns true if one was registered.
+    pub fn remove_host(&self, hostname: &str) -> bool {
+        self.by_host
+            .write()
+            .unwrap()
+            .remove(&hostname.to_ascii_lowercase())
+            .is_some()
+    }
+
+    fn certified_key(
+        &self,
+        cert_pem: &[u8],
+        key_pem: &[u8],
+    ) -> Result<Arc<CertifiedKey>, BoltError> {
+        let certs = parse_certs(cert_pem)?;
+        if certs.is_empty() {
+            return Err(BoltError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "no certificates found in PEM input",
+            )));
+        }
+        let key = parse_key(key_pem)?;
+        let key = CertifiedKey::from_der(certs, key, &self.provider).map_err(invalid_data)?;
+        Ok(Arc::new(key))
+    }
+
+    fn lookup(&self, server_name: &str) -> Option<Arc<CertifiedKey>> {
+        let name = server_name.to_ascii_lowercase();
+        let by_host = self.by_host.read().unwrap();
+        if let Some(key) = by_host.get(&name) {
+            return Some(key.clone());
+        }
+        let (_, parent) = name.split_once('.')?;
+        by_host.get(&format!("*.{parent}")).cloned()
+    }
+}
+
+impl Default for CertificateResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResolvesServerCert for CertificateResolver {
+    fn resolve(&self, client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        client_hello
+            .server_name()
+            .and_then(|name| self.lookup(name))
+            .or_else(|| self.default.read().unwrap().clone())
+    }
+}
+
+/// TLS configuration for the Bolt server.
+pub struct TlsConfig {
+    pub(crate) acceptor: TlsAcceptor,
+    resolver: Arc<CertificateResolver>,
+}
+
+impl TlsConfig {
+    /// Creates a TLS configuration from PEM-encoded certificate and key bytes.
+    pub fn from_pem(cert_pem: &[u8], key_pem: &[u8]) -> Result<Self, BoltError> {
+        Self::from_resolver(Arc::new(CertificateResolver::from_pem(cert_pem, key_pem)?))
+    }
+
+    /// Creates a TLS configuration that verifies client certificates (mutual TLS).
+    ///
+    /// `client_ca_pem` holds the PEM-encoded CA bundle client certificates are
+    /// verified against. The verified certificate is exposed to the
+    /// [`AuthValidator`](crate::server::AuthValidator) through
+    /// [`AuthCredentials::peer`](crate::server::AuthCredentials::peer), so a
+    /// LOGON with scheme `none` can be accepted based on the certificate identity.
+    pub fn from_pem_with_client_auth(
+        cert_pem: &[u8],
+        key_pem: &[u8],
+        client_ca_pem: &[u8],
+        client_auth: ClientAuth,
+    ) -> Result<Self, BoltError> {
+        Self::from_resolver_with_client_auth(
+            Arc::new(CertificateResolver::from_pem(cert_pem, key_pem)?),
+            client_ca_pem,
+            client_auth,
+        )
+    }
+
+    /// Creates a TLS configuration serving certificates from a shared resolver.
+    pub fn from_resolver(resolver: Arc<CertificateResolver>) -> Result<Self, BoltError> {
+        let config = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_cert_resolver(resolver.clone());
+        Ok(Self {
+            acceptor: TlsAcceptor::from(Arc::new(config)),
+            resolver,
+        })
+    }
+
+    /// Creates a mutual TLS configuration serving certificates from a shared resolver.
+    pub fn from_resolver_with_client_auth(
+        resolver: Arc<CertificateResolver>,
+        client_ca_pem: &[u8],
+        client_auth: ClientAuth,
+    ) -> Result<Self, BoltError> {
+        let mut roots = RootCertStore::empty();
+        for ca in parse_certs(client_ca_pem)? {
+            roots.add(ca).map_err(invalid_data)?;
+        }
+        let verifier = WebPkiClientVerifier::builder(Arc::new(roots));
+        let verifier = match client_auth {
+            ClientAuth::Optional => verifier.allow_unauthenticated().build(),
+            ClientAuth::Required => verifier.build(),
+        }
+        .map_err(invalid_data)?;
+
+        let config = ServerConfig::builder()
+            .with_client_cert_verifier(verifier)
+            .with_cert_resolver(resolver.clone());
+        Ok(Self {
+            acceptor: TlsAcceptor::from(Arc::new(config)),
+            resolver,
+        })
+    }
+
+    /// Returns the resolver used to rotate certificates at runtime.
+    pub fn resolver(&self) -> &Arc<CertificateResolver> {
+        &self.resolver
+    }
+}
+
+fn parse_certs(pem: &[u8]) -> Result<Vec<CertificateDer<'static>>, BoltError> {
+    CertificateDer::pem_slice_iter(pem)
+        .collect::<Result<_, _>>()
+        .map_err(invalid_data)
+}
+
+fn parse_key(pem: &[u8]) -> Result<PrivateKeyDer<'static>, BoltError> {
+    PrivateKeyDer::from_pem_slice(pem).map_err(invalid_data)
+}
+
+fn invalid_data(e: impl std::error::Error + Send + Sync + 'static) -> BoltError {
+    BoltError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
+/// Extracts the verified client certificate from an accepted TLS stream.
+pub(crate) fn peer_certificate<S>(
+    stream: &tokio_rustls::server::TlsStream<S>,
+) -> Option<PeerCertificate> {
+    let der = stream.get_ref().1.peer_certificates()?.first()?;
+    match PeerCertificate::from_der(der) {
+        Ok(cert) => Some(cert),
+        Err(e) => {
+            tracing::debug!(error = %e, "failed to parse client certificate");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rcgen::{BasicConstraints, CertificateParams, CertifiedIssuer, DnType, IsCa, KeyPair};
+    use tokio_rustls::TlsConnector;
+    use tokio_rustls::rustls::ClientConfig;
+
+    struct Pki {
+        ca: CertifiedIssuer<'static, KeyPair>,
+        server_cert: String,
+        server_key: String,
+    }
+
+    fn pki() -> Pki {
+        let mut ca_params = CertificateParams::new(vec![]).unwrap();
+        ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let ca = CertifiedIssuer::self_signed(ca_params, KeyPair::generate().unwrap()).unwrap();
+        let (server_cert, server_key) = server_identity(&ca, "localhost");
+        Pki {
+            ca,
+            server_cert,
+            server_key,
+        }
+    }
+
+    fn server_identity(ca: &CertifiedIssuer<'static, KeyPair>, host: &str) -> (String, String) {
+        let key = KeyPair::generate().unwrap();
+        let cert = CertificateParams::new(vec![host.to_string()])
+            .unwrap()
+            .signed_by(&key, ca)
+            .unwrap();
+        (cert.pem(), key.serialize_pem())
+    }
+
+    fn client_config(pki: &Pki, identity: Option<&str>) -> ClientConfig {
+        let mut roots = RootCertStore::empty();
+        roots.add(pki.ca.der().clone()).unwrap();
+        let builder = ClientConfig::builder().with_root_certificates(roots);
+        match identity {
+            Some(name) => {
+                let key = KeyPair::generate().unwrap();
+                let mut params = CertificateParams::new(vec![format!("{name}.internal")]).unwrap();
+                params.distinguished_name.push(DnType::CommonName, name);
+                let cert = params.signed_by(&key, &pki.ca).unwrap();
+                let key = PrivateKeyDer::from_pem_slice(key.serialize_pem().as_bytes()).unwrap();
+                builder
+                    .with_client_auth_cert(vec![cert.der().clone()], key)
+                    .unwrap()
+            }
+            None => builder.with_no_client_auth(),
+        }
+    }
+
+    async fn connect(
+        config: &TlsConfig,
+        client: ClientConfig,
+    ) -> Result<Option<PeerCertificate>, std::io::Error> {
+        handshake(config, client, "localhost").await.0
+    }
+
+    /// Runs a TLS handshake, returning the server-side client certificate
+    /// and the certificate the client was served.
+    async fn handshake(
+        config: &TlsConfig,
+        client: ClientConfig,
+        server_name: &str,
+    ) -> (
+        Result<Option<PeerCertificate>, std::io::Error>,
+        Option<CertificateDer<'static>>,
+    ) {
+        let (client_io, server_io) = tokio::io::duplex(16 * 1024);
+        let connector = TlsConnector::from(Arc::new(client));
+        let name = rustls_pki_types::ServerName::try_from(server_name.to_string()).unwrap();
+        let client_task = tokio::spawn(async move { connector.connect(name, client_io).await });
+        let server = config.acceptor.accept(server_io).await;
+        let served = client_task.await.unwrap().ok().and_then(|stream| {
+            stream
+                .get_ref()
+                .1
+                .peer_certificates()
+                .and_then(|certs| certs.first().cloned())
+        });
+        (server.map(|stream| peer_certificate(&stream)), served)
+    }
+
+    fn leaf_der(cert_pem: &str) -> CertificateDer<'static> {
+        parse_certs(cert_pem.as_bytes()).unwrap().remove(0)
+    }
+
+    #[tokio::test]
+    async fn rotated_certificate_applies_to_new_handshakes() {
+        let pki = pki();
+        let config =
+            TlsConfig::from_pem(pki.server_cert.as_bytes(), pki.server_key.as_bytes()).unwrap();
+
+        let (_, served) = handshake(&config, client_config(&pki, None), "localhost").await;
+        assert_eq!(served, Some(leaf_der(&pki.server_cert)));
+
+        let (new_cert, new_key) = server_identity(&pki.ca, "localhost");
+        config
+            .resolver()
+            .set_default_pem(new_cert.as_bytes(), new_key.as_bytes())
+            .unwrap();
+
+        let (_, served) = handshake(&config, client_config(&pki, None), "localhost").await;
+        assert_eq!(served, Some(leaf_der(&new_cert)));
+    }
+
+    #[tokio::test]
+    async fn sni_selects_host_certificate() {
+        let pki = pki();
+        let config =
+            TlsConfig::from_pem(pki.server_cert.as_bytes(), pki.server_key.as_bytes()).unwrap();
+        let (exact_cert, exact_key) = server_identity(&pki.ca, "graph-a.example.com");
+        let (wild_cert, wild_key) = server_identity(&pki.ca, "*.example.com");
+        let resolver = config.resolver();
+        resolver
+            .set_host_pem(
+                "graph-a.example.com",
+                exact_cert.as_bytes(),
+                exact_key.as_bytes(),
+            )
+            .unwrap();
+        resolver
+            .set_host_pem("*.example.com", wild_cert.as_bytes(), wild_key.as_bytes())
+            .unwrap();
+
+        let (_, served) =
+            handshake(&config, client_config(&pki, None), "graph-a.example.com").await;
+        assert_eq!(served, Some(leaf_der(&exact_cert)));
+
+        let (_, served) =
+            handshake(&config, client_config(&pki, None), "graph-b.example.com").await;
+        assert_eq!(served, Some(leaf_der(&wild_cert)));
+
+        assert!(resolver.remove_host("graph-a.example.com"));
+        let (_, served) =
+            handshake(&config, client_config(&pki, None), "graph-a.example.com").await;
+        assert_eq!(served, Some(leaf_der(&wild_cert)));
+    }
+
+    #[tokio::test]
+    async fn mutual_tls_exposes_client_identity() {
+        let pki = pki();
+        let config = TlsConfig::from_pem_with_client_auth(
+            pki.server_cert.as_bytes(),
+            pki.server_key.as_bytes(),
+            pki.ca.pem().as_bytes(),
+            ClientAuth::Required,
+        )
+        .unwrap();
+
+        let cert = connect(&config, client_config(&pki, Some("svc-a")))
+            .await
+            .unwrap()
+            .expect("client certificate");
+        assert_eq!(cert.common_name(), Some("svc-a"));
+        assert_eq!(cert.subject_alt_names, vec!["svc-a.internal".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn required_client_auth_rejects_anonymous_clients() {
+        let pki = pki();
+        let required = TlsConfig::from_pem_with_client_auth(
+            pki.server_cert.as_bytes(),
+            pki.server_key.as_bytes(),
+            pki.ca.pem().as_bytes(),
+            ClientAuth::Required,
+        )
+        .unwrap();
+        assert!(connect(&required, client_config(&pki, None)).await.is_err());
+
+        let optional = TlsConfig::from_pem_with_client_auth(
+            pki.server_cert.as_bytes(),
+            pki.server_key.as_bytes(),
+            pki.ca.pem().as_bytes(),
+            ClientAuth::Optional,
+        )
+        .unwrap();
+        let cert = connect(&optional, client_config(&pki, None)).await.unwrap();
+        assert!(cert.is_none());
+    }
+}
@@ -0,0 +1,145 @@
+//! Conversions from Bolt values to Rust types.
+
+use std::collections::HashMap;
+
+use crate::error::BoltError;
+
+use super::value::{
+    BoltDate, BoltDateTime, BoltDateTimeZoneId, BoltDuration, BoltLocalDateTime, BoltLocalTime,
+    BoltNode, BoltPath, BoltPoint2D, BoltPoint3D, BoltRelationship, BoltTime,
+    BoltUnboundRelationship, BoltValue,
+};
+
+/// Converts a [`BoltValue`] into a Rust type, failing with
+/// [`BoltError::Conversion`] if the value has another type.
+///
+/// Integers do not convert to floats or the reverse; `null` converts only
+/// to `Option` (as `None`) and `BoltValue`.
+///
+/// ```
+/// use boltr::types::{BoltValue, FromBoltValue};
+///
+/// let names = BoltValue::List(vec!["Ada".into(), "Alan".into()]);
+/// assert_eq!(Vec::<String>::from_bolt(names).unwrap(), ["Ada", "Alan"]);
+/// assert_eq!(Option::<i64>::from_bolt(BoltValue::Null).unwrap(), None);
+/// assert!(i64::from_bolt(BoltValue::Float(1.5)).is_err());
+/// ```
+pub trait FromBoltValue: Sized {
+    fn from_bolt(value: BoltValue) -> Result<Self, BoltError>;
+}
+
+fn mismatch(expected: &str, value: &BoltValue) -> BoltError {
+    BoltError::Conversion(format!("expected {expected}, got {value}"))
+}
+
+impl FromBoltValue for BoltValue {
+    fn from_bolt(value: BoltValue) -> Result<Self, BoltError> {
+        Ok(value)
+    }
+}
+
+impl<T: FromBoltValue> FromBoltValue for Option<T> {
+    fn from_bolt(value: BoltValue) -> Result<Self, BoltError> {
+        match value {
+            BoltValue::Null => Ok(None),
+            value => T::from_bolt(value).map(Some),
+        }
+    }
+}
+
+impl<T: FromBoltValue> FromBoltValue for Vec<T> {
+    fn from_bolt(value: BoltValue) -> Result<Self, BoltError> {
+        match value {
+            BoltValue::List(items) => items.into_iter().map(T::from_bolt).collect(),
+            other => Err(mismatch("a list", &other)),
+        }
+    }
+}
+
+impl<T: FromBoltValue> FromBoltValue for HashMap<String, T> {
+    fn from_bolt(value: BoltValue) -> Result<Self, BoltError> {
+        match value {
+            BoltValue::Dict(dict) => dict
+                .into_iter()
+                .map(|(key, value)| Ok((key, T::from_bolt(value)?)))
+                .collect(),
+            other => Err(mismatch("a map", &other)),
+        }
+    }
+}
+
+/// Bolt bytes, as a [`FromBoltValue`] target. A newtype rather than
+/// `Vec<u8>`, which converts from lists like other vectors.
+///
+/// ```
+/// use boltr::types::{BoltValue, Bytes, FromBoltValue};
+///
+/// let bytes = Bytes::from_bolt(BoltValue::Bytes(vec![0xCA, 0xFE])).unwrap();
+/// assert_eq!(Vec::from(bytes), [0xCA, 0xFE]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Bytes(pub Vec<u8>);
+
+impl From<Bytes> for Vec<u8> {
+    fn from(bytes: Bytes) -> Self {
+        bytes.0
+    }
+}
+
+impl AsRef<[u8]> for Bytes {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl FromBoltValue for Bytes {
+    fn from_bolt(value: BoltValue) -> Result<Self, BoltError> {
+        match value {
+            BoltValue::Bytes(bytes) => Ok(Self(bytes)),
+            other => Err(mismatch("bytes", &other)),
+        }
+    }
+}
+
+impl FromBoltValue for i32 {
+    fn from_bolt(value: BoltValue) -> Result<Self, BoltError> {
+        let i = i64::from_bolt(value)?;
+        i32::try_from(i).map_err(|_| BoltError::Conversion(format!("{i} does not fit in i32")))
+    }
+}
+
+/// Implements [`FromBoltValue`] for the payload of a single variant.
+macro_rules! from_variant {
+    ($($ty:ty => $variant:ident, $expected:literal;)*) => {
+        $(
+            impl FromBoltValue for $ty {
+                fn from_bolt(value: BoltValue) -> Result<Self, BoltError> {
+                    match value {
+                        BoltValue::$variant(v) => Ok(v),
+                        other => Err(mismatch($expected, &other)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+from_variant! {
+    bool => Boolean, "a boolean";
+    i64 => Integer, "an integer";
+    f64 => Float, "a float";
+    String => String, "a string";
+    BoltNode => Node, "a node";
+    BoltRelationship => Relationship, "a relationship";
+    BoltUnboundRelationship => UnboundRelationship, "a relationship";
+    BoltPath => Path, "a path";
+    BoltDate => Date, "a date";
+    BoltTime => Time, "a time";
+    BoltLocalTime => LocalTime, "a local time";
+    BoltDateTime => DateTime, "a datetime";
+    BoltDateTimeZoneId => DateTimeZoneId, "a datetime";
+    BoltLocalDateTime => LocalDateTime, "a local datetime";
+    BoltDuration => Duration, "a duration";
+    BoltPoint2D => Point2D, "a 2D point";
+    BoltPoint3D => Point3D, "a 3D point";
+}
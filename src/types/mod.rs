@@ -1,7 +1,13 @@
 //! Bolt protocol value types and graph structures.
 
+mod convert;
+mod summary;
 mod value;
 
+pub use convert::{Bytes, FromBoltValue};
+pub(crate) use summary::millis;
+pub use summary::{Plan, ProfiledPlan, QueryStatistics, QueryType, ResultSummary};
+
 pub use value::{
     BoltDate, BoltDateTime, BoltDateTimeZoneId, BoltDict, BoltDuration, BoltLocalDateTime,
     BoltLocalTime, BoltNode, BoltPath, BoltPoint2D, BoltPoint3D, BoltRelationship, BoltTime,
@@ -0,0 +1,488 @@
+//! Result summary types shared by the server and the client.
+//!
+//! [`ResultSummary`] is what a backend returns with a result and what the
+//! client parses from the final PULL (or DISCARD) reply. It converts to and
+//! from the summary metadata drivers expect: `type`, `stats` (hyphenated
+//! counter names), `plan`/`profile` trees, `db`, `t_first` and `t_last`.
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::types::{BoltDict, BoltValue};
+
+/// What a query did, reported as `type` in the final PULL or DISCARD summary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QueryType {
+    /// Only read data (`"r"`).
+    Read,
+    /// Only wrote data (`"w"`).
+    Write,
+    /// Read and wrote data (`"rw"`).
+    ReadWrite,
+    /// Changed the schema, e.g. created an index (`"s"`).
+    Schema,
+}
+
+impl QueryType {
+    /// The code used on the wire.
+    ///
+    /// ```
+    /// use boltr::types::QueryType;
+    ///
+    /// assert_eq!(QueryType::ReadWrite.as_str(), "rw");
+    /// assert_eq!(QueryType::parse("s"), Some(QueryType::Schema));
+    /// assert_eq!(QueryType::parse("x"), None);
+    /// ```
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Read => "r",
+            Self::Write => "w",
+            Self::ReadWrite => "rw",
+            Self::Schema => "s",
+        }
+    }
+
+    /// Parses a wire code; returns `None` for anything unknown.
+    #[must_use]
+    pub fn parse(code: &str) -> Option<Self> {
+        match code {
+            "r" => Some(Self::Read),
+            "w" => Some(Self::Write),
+            "rw" => Some(Self::ReadWrite),
+            "s" => Some(Self::Schema),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for QueryType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Update counters of a query, sent as the `stats` summary entry.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueryStatistics {
+    pub nodes_created: i64,
+    pub nodes_deleted: i64,
+    pub relationships_created: i64,
+    pub relationships_deleted: i64,
+    pub properties_set: i64,
+    pub labels_added: i64,
+    pub labels_removed: i64,
+    pub indexes_added: i64,
+    pub indexes_removed: i64,
+    pub constraints_added: i64,
+    pub constraints_removed: i64,
+    /// Updates to the `system` database, e.g. user or database management.
+    pub system_updates: i64,
+}
+
+impl QueryStatistics {
+    /// Whether any data or schema was changed (system updates aside).
+    #[must_use]
+    pub fn contains_updates(&self) -> bool {
+        self.counters()[..11].iter().any(|&(_, n)| n != 0)
+    }
+
+    /// Whether the `system` database was changed.
+    #[must_use]
+    pub fn contains_system_updates(&self) -> bool {
+        self.system_updates != 0
+    }
+
+    /// Counters under their wire names; `system-updates` is last.
+    fn counters(&self) -> [(&'static str, i64); 12] {
+        [
+            ("nodes-created", self.nodes_created),
+            ("nodes-deleted", self.nodes_deleted),
+            ("relationships-created", self.relationships_created),
+            ("relationships-deleted", self.relationships_deleted),
+            ("properties-set", self.properties_set),
+            ("labels-added", self.labels_added),
+            ("labels-removed", self.labels_removed),
+            ("indexes-added", self.indexes_added),
+            ("indexes-removed", self.indexes_removed),
+            ("constraints-added", self.constraints_added),
+            ("constraints-removed", self.constraints_removed),
+            ("system-updates", self.system_updates),
+        ]
+    }
+
+    fn counter_mut(&mut self, key: &str) -> Option<&mut i64> {
+        Some(match key {
+            "nodes-created" => &mut self.nodes_created,
+            "nodes-deleted" => &mut self.nodes_deleted,
+            "relationships-created" => &mut self.relationships_created,
+            "relationships-deleted" => &mut self.relationships_deleted,
+            "properties-set" => &mut self.properties_set,
+            "labels-added" => &mut self.labels_added,
+            "labels-removed" => &mut self.labels_removed,
+            "indexes-added" => &mut self.indexes_added,
+            "indexes-removed" => &mut self.indexes_removed,
+            "constraints-added" => &mut self.constraints_added,
+            "constraints-removed" => &mut self.constraints_removed,
+            "system-updates" => &mut self.system_updates,
+            _ => return None,
+        })
+    }
+
+    /// The `stats` dictionary: non-zero counters plus the
+    /// `contains-updates` / `contains-system-updates` flags, or `None` if
+    /// nothing changed.
+    #[must_use]
+    pub fn to_metadata(&self) -> Option<BoltDict> {
+        let mut stats: BoltDict = self
+            .counters()
+            .into_iter()
+            .filter(|&(_, n)| n != 0)
+            .map(|(key, n)| (key.to_string(), BoltValue::Integer(n)))
+            .collect();
+        if stats.is_empty() {
+            return None;
+        }
+        if self.contains_updates() {
+            stats.insert("contains-updates".into(), BoltValue::Boolean(true));
+        }
+        if self.contains_system_updates() {
+            stats.insert("contains-system-updates".into(), BoltValue::Boolean(true));
+        }
+        Some(stats)
+    }
+
+    /// Parses a `stats` dictionary; unknown and missing entries are ignored.
+    #[must_use]
+    pub fn from_metadata(stats: &BoltDict) -> Self {
+        let mut counters = Self::default();
+        for (key, value) in stats {
+            if let (Some(counter), Some(n)) = (counters.counter_mut(key), value.as_int()) {
+                *counter = n;
+            }
+        }
+        counters
+    }
+}
+
+/// An execution plan, sent as the `plan` summary entry of `EXPLAIN` queries.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Plan {
+    pub operator_type: String,
+    /// Operator arguments, e.g. `EstimatedRows` or `Details`.
+    pub arguments: BoltDict,
+    /// Variables the operator produces.
+    pub identifiers: Vec<String>,
+    pub children: Vec<Plan>,
+}
+
+impl Plan {
+    #[must_use]
+    pub fn to_metadata(&self) -> BoltDict {
+        plan_dict(
+            &self.operator_type,
+            &self.arguments,
+            &self.identifiers,
+            self.children.iter().map(Self::to_metadata),
+        )
+    }
+
+    /// Parses a `plan` dictionary; returns `None` without `operatorType`.
+    #[must_use]
+    pub fn from_metadata(plan: &BoltDict) -> Option<Self> {
+        Some(Self {
+            operator_type: plan.get("operatorType")?.as_str()?.to_string(),
+            arguments: dict(plan, "args"),
+            identifiers: strings(plan, "identifiers"),
+            children: children(plan).filter_map(Self::from_metadata).collect(),
+        })
+    }
+}
+
+/// An executed plan with runtime statistics, sent as the `profile`
+/// summary entry of `PROFILE` queries.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProfiledPlan {
+    pub operator_type: String,
+    pub arguments: BoltDict,
+    pub identifiers: Vec<String>,
+    pub db_hits: i64,
+    /// Rows produced by the operator.
+    pub rows: i64,
+    pub page_cache_hits: i64,
+    pub page_cache_misses: i64,
+    pub page_cache_hit_ratio: f64,
+    /// Time spent in the operator, in milliseconds.
+    pub time: i64,
+    pub children: Vec<ProfiledPlan>,
+}
+
+impl ProfiledPlan {
+    #[must_use]
+    pub fn to_metadata(&self) -> BoltDict {
+        let mut profile = plan_dict(
+            &self.operator_type,
+            &self.arguments,
+            &self.identifiers,
+            self.children.iter().map(Self::to_metadata),
+        );
+        for (key, n) in [
+            ("dbHits", self.db_hits),
+            ("rows", self.rows),
+            ("pageCacheHits", self.page_cache_hits),
+            ("pageCacheMisses", self.page_cache_misses),
+            ("time", self.time),
+        ] {
+            profile.insert(key.into(), BoltValue::Integer(n));
+        }
+        profile.insert(
+            "pageCacheHitRatio".into(),
+            BoltValue::Float(self.page_cache_hit_ratio),
+        );
+        profile
+    }
+
+    /// Parses a `profile` dictionary; returns `None` without `operatorType`.
+    /// Missing statistics are zero.
+    #[must_use]
+    pub fn from_metadata(profile: &BoltDict) -> Option<Self> {
+        let int = |key: &str| profile.get(key).and_then(BoltValue::as_int).unwrap_or(0);
+        Some(Self {
+            operator_type: profile.get("operatorType")?.as_str()?.to_string(),
+            arguments: dict(profile, "args"),
+            identifiers: strings(profile, "identifiers"),
+            db_hits: int("dbHits"),
+            rows: int("rows"),
+            page_cache_hits: int("pageCacheHits"),
+            page_cache_misses: int("pageCacheMisses"),
+            page_cache_hit_ratio: match profile.get("pageCacheHitRatio") {
+                Some(BoltValue::Float(ratio)) => *ratio,
+                _ => 0.0,
+            },
+            time: int("time"),
+            children: children(profile).filter_map(Self::from_metadata).collect(),
+        })
+    }
+}
+
+/// The summary of a query result.
+///
+/// Backends fill in what they know; the server adds `db` and the timings if
+/// they are left empty. Entries without a typed field, such as
+/// `notifications`, go in [`extra`](Self::extra).
+///
+/// ```
+/// use boltr::types::{BoltValue, QueryStatistics, QueryType, ResultSummary};
+///
+/// let summary = ResultSummary {
+///     query_type: Some(QueryType::Write),
+///     counters: QueryStatistics { nodes_created: 2, ..Default::default() },
+///     ..Default::default()
+/// };
+/// let metadata = summary.to_metadata();
+/// assert_eq!(metadata["type"], BoltValue::String("w".into()));
+/// assert_eq!(ResultSummary::from_metadata(&metadata), summary);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResultSummary {
+    pub query_type: Option<QueryType>,
+    pub counters: QueryStatistics,
+    pub plan: Option<Plan>,
+    pub profile: Option<ProfiledPlan>,
+    /// Database the query ran on.
+    pub database: Option<String>,
+    /// Time until the result was available (`t_first`).
+    pub result_available_after: Option<Duration>,
+    /// Time to stream the result once available (`t_last`).
+    pub result_consumed_after: Option<Duration>,
+    /// Other summary entries, passed through unchanged.
+    pub extra: BoltDict,
+}
+
+impl ResultSummary {
+    /// The summary as PULL SUCCESS metadata.
+    #[must_use]
+    pub fn to_metadata(&self) -> BoltDict {
+        let mut metadata = self.extra.clone();
+        if let Some(query_type) = self.query_type {
+            metadata.insert("type".into(), BoltValue::String(query_type.as_str().into()));
+        }
+        if let Some(stats) = self.counters.to_metadata() {
+            metadata.insert("stats".into(), BoltValue::Dict(stats));
+        }
+        if let Some(ref plan) = self.plan {
+            metadata.insert("plan".into(), BoltValue::Dict(plan.to_metadata()));
+        }
+        if let Some(ref profile) = self.profile {
+            metadata.insert("profile".into(), BoltValue::Dict(profile.to_metadata()));
+        }
+        if let Some(ref db) = self.database {
+            metadata.insert("db".into(), BoltValue::String(db.clone()));
+        }
+        if let Some(t_first) = self.result_available_after {
+            metadata.insert("t_first".into(), millis(t_first));
+        }
+        if let Some(t_last) = self.result_consumed_after {
+            metadata.insert("t_last".into(), millis(t_last));
+        }
+        metadata
+    }
+
+    /// Parses summary metadata. `has_more` is dropped; unknown entries are
+    /// kept in [`extra`](Self::extra).
+    #[must_use]
+    pub fn from_metadata(metadata: &BoltDict) -> Self {
+        let mut summary = Self::default();
+        for (key, value) in metadata {
+            match (key.as_str(), value) {
+                ("type", BoltValue::String(code)) => summary.query_type = QueryType::parse(code),
+                ("stats", BoltValue::Dict(stats)) => {
+                    summary.counters = QueryStatistics::from_metadata(stats);
+                }
+                ("plan", BoltValue::Dict(plan)) => summary.plan = Plan::from_metadata(plan),
+                ("profile", BoltValue::Dict(profile)) => {
+                    summary.profile = ProfiledPlan::from_metadata(profile);
+                }
+                ("db", BoltValue::String(db)) => summary.database = Some(db.clone()),
+                ("t_first", BoltValue::Integer(ms)) => {
+                    summary.result_available_after =
+                        u64::try_from(*ms).ok().map(Duration::from_millis);
+                }
+                ("t_last", BoltValue::Integer(ms)) => {
+                    summary.result_consumed_after =
+                        u64::try_from(*ms).ok().map(Duration::from_millis);
+                }
+                ("has_more", _) => {}
+                _ => {
+                    summary.extra.insert(key.clone(), value.clone());
+                }
+            }
+        }
+        summary
+    }
+}
+
+/// A duration in whole milliseconds, as `t_first` and `t_last` report it.
+pub(crate) fn millis(duration: Duration) -> BoltValue {
+    BoltValue::Integer(i64::try_from(duration.as_millis()).unwrap_or(i64::MAX))
+}
+
+fn plan_dict(
+    operator_type: &str,
+    arguments: &BoltDict,
+    identifiers: &[String],
+    children: impl Iterator<Item = BoltDict>,
+) -> BoltDict {
+    BoltDict::from([
+        (
+            "operatorType".into(),
+            BoltValue::String(operator_type.into()),
+        ),
+        ("args".into(), BoltValue::Dict(arguments.clone())),
+        (
+            "identifiers".into(),
+            BoltValue::List(identifiers.iter().cloned().map(BoltValue::String).collect()),
+        ),
+        (
+            "children".into(),
+            BoltValue::List(children.map(BoltValue::Dict).collect()),
+        ),
+    ])
+}
+
+fn dict(metadata: &BoltDict, key: &str) -> BoltDict {
+    match metadata.get(key) {
+        Some(BoltValue::Dict(d)) => d.clone(),
+        _ => BoltDict::new(),
+    }
+}
+
+fn strings(metadata: &BoltDict, key: &str) -> Vec<String> {
+    match metadata.get(key) {
+        Some(BoltValue::List(items)) => items
+            .iter()
+            .filter_map(|v| v.as_str().map(String::from))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn children(metadata: &BoltDict) -> impl Iterator<Item = &BoltDict> {
+    let items = match metadata.get("children") {
+        Some(BoltValue::List(items)) => items.as_slice(),
+        _ => &[],
+    };
+    items.iter().filter_map(|v| match v {
+        BoltValue::Dict(d) => Some(d),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_use_wire_names_and_skip_zeros() {
+        let counters = QueryStatistics {
+            nodes_created: 3,
+            properties_set: 6,
+            ..Default::default()
+        };
+        let stats = counters.to_metadata().unwrap();
+        assert_eq!(stats.len(), 3);
+        assert_eq!(stats["nodes-created"], BoltValue::Integer(3));
+        assert_eq!(stats["contains-updates"], BoltValue::Boolean(true));
+        assert_eq!(QueryStatistics::from_metadata(&stats), counters);
+        assert_eq!(QueryStatistics::default().to_metadata(), None);
+
+        let system = QueryStatistics {
+            system_updates: 1,
+            ..Default::default()
+        };
+        assert!(!system.contains_updates());
+        let stats = system.to_metadata().unwrap();
+        assert_eq!(stats["contains-system-updates"], BoltValue::Boolean(true));
+    }
+
+    #[test]
+    fn profiles_round_trip() {
+        let profile = ProfiledPlan {
+            operator_type: "ProduceResults@neo4j".into(),
+            identifiers: vec!["n".into()],
+            db_hits: 0,
+            rows: 1,
+            children: vec![ProfiledPlan {
+                operator_type: "AllNodesScan@neo4j".into(),
+                arguments: BoltDict::from([("EstimatedRows".into(), BoltValue::Float(10.0))]),
+                db_hits: 11,
+                rows: 10,
+                page_cache_hit_ratio: 0.5,
+                time: 2,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let metadata = profile.to_metadata();
+        let BoltValue::List(ref children) = metadata["children"] else {
+            panic!("children is not a list");
+        };
+        let BoltValue::Dict(ref child) = children[0] else {
+            panic!("child is not a dict");
+        };
+        assert_eq!(child["dbHits"], BoltValue::Integer(11));
+        assert_eq!(
+            child["operatorType"],
+            BoltValue::String("AllNodesScan@neo4j".into())
+        );
+        assert_eq!(ProfiledPlan::from_metadata(&metadata), Some(profile));
+
+        // EXPLAIN plans carry no statistics.
+        let plan = Plan::from_metadata(&metadata).unwrap();
+        assert_eq!(
+            plan.children[0].arguments["EstimatedRows"],
+            BoltValue::Float(10.0)
+        );
+        assert!(!plan.to_metadata().contains_key("dbHits"));
+    }
+}
@@ -9,7 +9,8 @@ use tokio_tungstenite::WebSocketStream;
 use crate::error::BoltError;
 use crate::server::auth::AuthValidator;
 use crate::server::backend::BoltBackend;
-use crate::server::builder::run_handshake_and_connection;
+use crate::server::context::{Admission, ServerContext};
+use crate::server::peer::PeerInfo;
 use crate::server::session_manager::SessionManager;
 use crate::ws::WsStream;
 
@@ -57,16 +58,10 @@ pub fn accept_ws<S, B>(
     B: BoltBackend,
 {
     let adapted = WsStream::new(ws_stream);
+    let ctx = context(backend, session_manager, auth_validator, max_message_size);
     tokio::spawn(async move {
-        run_handshake_and_connection(
-            adapted,
-            peer_addr,
-            backend,
-            session_manager,
-            auth_validator,
-            max_message_size,
-        )
-        .await;
+        ctx.run_handshake_and_connection(adapted, PeerInfo::new(peer_addr), Admission::default())
+            .await;
     });
 }
 
@@ -88,14 +83,20 @@ where
     B: BoltBackend,
 {
     let adapted = WsStream::new(ws_stream);
-    run_handshake_and_connection(
-        adapted,
-        peer_addr,
-        backend,
-        session_manager,
-        auth_validator,
-        max_message_size,
-    )
-    .await;
+    let ctx = context(backend, session_manager, auth_validator, max_message_size);
+    ctx.run_handshake_and_connection(adapted, PeerInfo::new(peer_addr), Admission::default())
+        .await;
     Ok(())
 }
+
+fn context<B: BoltBackend>(
+    backend: Arc<B>,
+    session_manager: Arc<SessionManager>,
+    auth_validator: Option<Arc<dyn AuthValidator>>,
+    max_message_size: Option<usize>,
+) -> Arc<ServerContext<B>> {
+    let mut ctx = ServerContext::new(backend, session_manager);
+    ctx.auth_validator = auth_validator;
+    ctx.max_message_size = max_message_size;
+    Arc::new(ctx)
+}